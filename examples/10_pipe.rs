@@ -1,3 +1,6 @@
+use aoc_2023::check::{shrink_usize, Checker};
+use aoc_2023::generate;
+use aoc_2023::rng::Rng;
 use std::{
     collections::{HashMap, HashSet},
     ops::{Deref, DerefMut},
@@ -47,6 +50,22 @@ impl std::fmt::Display for Pipe {
     }
 }
 
+impl Pipe {
+    /// Unicode box-drawing character for this pipe
+    fn box_char(&self) -> char {
+        match self {
+            Self::NorthSouth => '│',
+            Self::EastWest => '─',
+            Self::NorthEast => '└',
+            Self::NorthWest => '┘',
+            Self::SouthWest => '┐',
+            Self::SouthEast => '┌',
+            Self::Ground => '.',
+            Self::Start => 'S',
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Scenario(Vec<Vec<Pipe>>);
 
@@ -196,23 +215,47 @@ fn exercise1(scenario: &mut Scenario) -> isize {
             }
         }
     }
-    // guess start pipe type and set it
-    let start_north = (start.0 - 1, start.1);
-    let start_south = (start.0 + 1, start.1);
-    let start_east = (start.0, start.1 + 1);
-    let start_west = (start.0, start.1 - 1);
+    // guess start pipe type and set it. The loop may pass next to the start without
+    // connecting to it, so only neighbours with a pipe towards the start count
+    let connects = |row: Option<usize>, col: Option<usize>, pipes: [Pipe; 3]| {
+        let (Some(row), Some(col)) = (row, col) else {
+            return false;
+        };
+        let pipe = scenario.get(row).and_then(|line| line.get(col));
+        paths.contains_key(&(row, col)) && pipe.is_some_and(|pipe| pipes.contains(pipe))
+    };
+    let start_north = connects(
+        start.0.checked_sub(1),
+        Some(start.1),
+        [Pipe::NorthSouth, Pipe::SouthEast, Pipe::SouthWest],
+    );
+    let start_south = connects(
+        Some(start.0 + 1),
+        Some(start.1),
+        [Pipe::NorthSouth, Pipe::NorthEast, Pipe::NorthWest],
+    );
+    let start_east = connects(
+        Some(start.0),
+        Some(start.1 + 1),
+        [Pipe::EastWest, Pipe::NorthWest, Pipe::SouthWest],
+    );
+    let start_west = connects(
+        Some(start.0),
+        start.1.checked_sub(1),
+        [Pipe::EastWest, Pipe::NorthEast, Pipe::SouthEast],
+    );
 
-    if paths.contains_key(&start_north) && paths.contains_key(&start_south) {
+    if start_north && start_south {
         scenario[start.0][start.1] = Pipe::NorthSouth;
-    } else if paths.contains_key(&start_east) && paths.contains_key(&start_west) {
+    } else if start_east && start_west {
         scenario[start.0][start.1] = Pipe::EastWest;
-    } else if paths.contains_key(&start_north) && paths.contains_key(&start_east) {
+    } else if start_north && start_east {
         scenario[start.0][start.1] = Pipe::NorthEast;
-    } else if paths.contains_key(&start_north) && paths.contains_key(&start_west) {
+    } else if start_north && start_west {
         scenario[start.0][start.1] = Pipe::NorthWest;
-    } else if paths.contains_key(&start_south) && paths.contains_key(&start_east) {
+    } else if start_south && start_east {
         scenario[start.0][start.1] = Pipe::SouthEast;
-    } else if paths.contains_key(&start_south) && paths.contains_key(&start_west) {
+    } else if start_south && start_west {
         scenario[start.0][start.1] = Pipe::SouthWest;
     } else {
         panic!("cannot guess start pipe type");
@@ -221,8 +264,7 @@ fn exercise1(scenario: &mut Scenario) -> isize {
     *paths.values().max().unwrap()
 }
 
-fn exercise2(scenario: &Scenario) -> usize {
-    // find inside tiles
+fn inside_tiles(scenario: &Scenario) -> HashSet<(usize, usize)> {
     let mut candidates = HashSet::new();
     for (row, line) in scenario.iter().enumerate() {
         let mut prev = None;
@@ -243,32 +285,214 @@ fn exercise2(scenario: &Scenario) -> usize {
                 _ => {}
             }
         }
-        println!();
     }
+    candidates
+}
+
+fn exercise2(scenario: &Scenario) -> usize {
+    inside_tiles(scenario).len()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Region {
+    Loop,
+    Inside,
+    Outside,
+    Unused,
+}
+
+impl Region {
+    /// ANSI escape sequence used to colour tiles of this region
+    fn ansi(&self) -> &'static str {
+        match self {
+            Self::Loop => "\x1b[0;1;37m",
+            Self::Inside => "\x1b[0;1;32m",
+            Self::Outside => "\x1b[0;2;34m",
+            Self::Unused => "\x1b[0;33m",
+        }
+    }
+
+    /// Character used for non-loop tiles when colours are disabled
+    fn plain(&self) -> char {
+        match self {
+            Self::Inside => 'I',
+            Self::Outside => 'O',
+            Self::Unused => '*',
+            Self::Loop => unreachable!("loop tiles are drawn with their pipe"),
+        }
+    }
+}
+
+/// Draws a pipe map with box-drawing characters, highlighting the main loop
+/// and the tiles it encloses.
+struct Renderer {
+    tiles: Vec<Vec<(Pipe, Region)>>,
+    colour: bool,
+    max_width: Option<usize>,
+}
 
-    // candidates = candidates.difference(&outside).cloned().collect();
+impl Renderer {
+    /// `original` is the map as parsed, `cleaned` is the map after `exercise1`
+    /// (only the loop remains, with the start tile replaced by its actual pipe).
+    fn new(original: &Scenario, cleaned: &Scenario, inside: &HashSet<(usize, usize)>) -> Self {
+        let mut tiles = Vec::new();
+        for (row, line) in cleaned.iter().enumerate() {
+            let mut tiles_row = Vec::new();
+            for (col, &pipe) in line.iter().enumerate() {
+                let tile = if pipe != Pipe::Ground {
+                    (pipe, Region::Loop)
+                } else if inside.contains(&(row, col)) {
+                    (original[row][col], Region::Inside)
+                } else if original[row][col] != Pipe::Ground {
+                    (original[row][col], Region::Unused)
+                } else {
+                    (Pipe::Ground, Region::Outside)
+                };
+                tiles_row.push(tile);
+            }
+            tiles.push(tiles_row);
+        }
+        Self {
+            tiles,
+            colour: true,
+            max_width: None,
+        }
+    }
 
+    /// Enables or disables ANSI colours
+    fn colour(mut self, colour: bool) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    /// Crops every line to at most `max_width` tiles
+    fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+}
+
+impl std::fmt::Display for Renderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in self.tiles.iter() {
+            let width = self.max_width.unwrap_or(line.len()).min(line.len());
+            let mut current = None;
+            for &(pipe, region) in line.iter().take(width) {
+                if !self.colour {
+                    match region {
+                        Region::Loop => write!(f, "{}", pipe.box_char())?,
+                        _ => write!(f, "{}", region.plain())?,
+                    }
+                    continue;
+                }
+                if current != Some(region) {
+                    write!(f, "{}", region.ansi())?;
+                    current = Some(region);
+                }
+                write!(f, "{}", pipe.box_char())?;
+            }
+            if current.is_some() {
+                write!(f, "\x1b[0m")?;
+            }
+            if width < line.len() {
+                write!(f, "…")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Tiles enclosed by the loop of a cleaned map, by flooding the outside of a map of twice
+/// the resolution, where the gaps between tiles are blocked where pipes connect
+fn brute_force(scenario: &Scenario) -> usize {
+    let (rows, cols) = (scenario.len(), scenario[0].len());
+    let mut blocked = vec![vec![false; 2 * cols + 1]; 2 * rows + 1];
     for (row, line) in scenario.iter().enumerate() {
-        for (col, &tile) in line.iter().enumerate() {
-            if candidates.contains(&(row, col)) {
-                assert!(tile == Pipe::Ground);
-                print!("I");
-            } else if tile != Pipe::Ground {
-                print!(" *");
-            } else {
-                print!(" ");
+        for (col, pipe) in line.iter().enumerate() {
+            let exits: &[(isize, isize)] = match pipe {
+                Pipe::NorthSouth => &[(-1, 0), (1, 0)],
+                Pipe::EastWest => &[(0, -1), (0, 1)],
+                Pipe::NorthEast => &[(-1, 0), (0, 1)],
+                Pipe::NorthWest => &[(-1, 0), (0, -1)],
+                Pipe::SouthWest => &[(1, 0), (0, -1)],
+                Pipe::SouthEast => &[(1, 0), (0, 1)],
+                _ => continue,
+            };
+            let (r, c) = (2 * row as isize + 1, 2 * col as isize + 1);
+            blocked[r as usize][c as usize] = true;
+            for (dr, dc) in exits {
+                blocked[(r + dr) as usize][(c + dc) as usize] = true;
+            }
+        }
+    }
+    let mut outside = vec![vec![false; 2 * cols + 1]; 2 * rows + 1];
+    outside[0][0] = true;
+    let mut pending = vec![(0usize, 0usize)];
+    while let Some((r, c)) = pending.pop() {
+        for (nr, nc) in [
+            (r + 1, c),
+            (r, c + 1),
+            (r.wrapping_sub(1), c),
+            (r, c.wrapping_sub(1)),
+        ] {
+            if nr <= 2 * rows && nc <= 2 * cols && !blocked[nr][nc] && !outside[nr][nc] {
+                outside[nr][nc] = true;
+                pending.push((nr, nc));
             }
         }
-        println!();
     }
+    let tiles = (0..rows).flat_map(|row| (0..cols).map(move |col| (row, col)));
+    tiles
+        .filter(|&(row, col)| scenario[row][col] == Pipe::Ground)
+        .filter(|&(row, col)| !outside[2 * row + 1][2 * col + 1])
+        .count()
+}
 
-    candidates.len()
+/// Compares the parity scan of exercise 2 with a flood fill, on generated loops. The
+/// inputs are `(size, seed)` of the generator, so shrinking makes the map smaller.
+fn check(checker: &Checker) -> bool {
+    checker.check(
+        "exercise 2",
+        |rng| (rng.range(2..16), rng.next_u64()),
+        |&(size, seed)| {
+            let smaller = shrink_usize(size).into_iter().filter(|&s| s >= 2);
+            smaller.map(|s| (s, seed)).collect()
+        },
+        |&(size, seed)| {
+            let input = generate::generate(10, size, &mut Rng::new(seed))
+                .unwrap()
+                .input;
+            let lines = input
+                .lines()
+                .map(|line| line.chars().map(Pipe::from).collect());
+            let mut scenario = Scenario(lines.collect());
+            exercise1(&mut scenario);
+            let (fast, slow) = (exercise2(&scenario), brute_force(&scenario));
+            match fast == slow {
+                true => Ok(()),
+                false => Err(format!("parity scan {}, flood fill {}", fast, slow)),
+            }
+        },
+    )
 }
 
 fn main() {
+    // `--check [cases] [seed]` compares exercise 2 with a flood fill instead
+    Checker::run_from_args(check);
+
     let mut scenario = parse_input("data/10_input.txt");
+    let original = scenario.clone();
     println!("{}", scenario);
     println!("exercise 1: {}", exercise1(&mut scenario));
-    println!("{}", scenario);
+
+    let inside = inside_tiles(&scenario);
+    let colour = std::env::var_os("NO_COLOR").is_none();
+    let renderer = Renderer::new(&original, &scenario, &inside)
+        .colour(colour)
+        .max_width(200);
+    println!("{}", renderer);
     println!("exercise 2: {}", exercise2(&scenario));
 }
+
+aoc_2023::check_test!(check);
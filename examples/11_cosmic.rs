@@ -84,13 +84,7 @@ fn expanded_space(scenario: &Scenario) -> (Vec<usize>, Vec<usize>) {
     (rows, cols)
 }
 
-fn exercise1(
-    scenario: &Scenario,
-    expanded_rows: &[usize],
-    expanded_cols: &[usize],
-    expansion: usize,
-) -> usize {
-    // find all galaxies
+fn find_galaxies(scenario: &Scenario) -> Vec<(usize, usize)> {
     let mut galaxies = Vec::new();
     for (row, line) in scenario.iter().enumerate() {
         for (col, item) in line.iter().enumerate() {
@@ -99,39 +93,118 @@ fn exercise1(
             }
         }
     }
+    galaxies
+}
 
-    let mut res = 0;
-    for (n, g1) in galaxies.iter().enumerate() {
-        for g2 in galaxies.iter().skip(n + 1) {
-            let (min_row, max_row) = (g1.0.min(g2.0), g1.0.max(g2.0));
-            let (min_col, max_col) = (g1.1.min(g2.1), g1.1.max(g2.1));
-
-            let mut distance = max_row - min_row + max_col - min_col;
-            for expanded_row in expanded_rows.iter() {
-                if (min_row..=max_row).contains(expanded_row) {
-                    distance += expansion - 1;
+/// Galaxy coordinates after expanding the empty rows and columns.
+struct Galaxies(Vec<(u128, u128)>);
+
+impl Galaxies {
+    /// Every empty row is replaced by `row_expansion` rows, and every empty column by
+    /// `col_expansion` columns (0 removes them). `expanded_rows` and `expanded_cols` must
+    /// be sorted.
+    fn new(
+        scenario: &Scenario,
+        expanded_rows: &[usize],
+        expanded_cols: &[usize],
+        row_expansion: u128,
+        col_expansion: u128,
+    ) -> Self {
+        // number of empty rows/columns before each index (prefix sums)
+        let prefix = |expanded: &[usize], len: usize| {
+            let mut res = vec![0u128; len];
+            let mut n_empty = 0;
+            for (i, r) in res.iter_mut().enumerate() {
+                *r = n_empty;
+                if expanded.binary_search(&i).is_ok() {
+                    n_empty += 1;
                 }
             }
-            for expanded_col in expanded_cols.iter() {
-                if (min_col..=max_col).contains(expanded_col) {
-                    distance += expansion - 1;
-                }
+            res
+        };
+        let empty_rows = prefix(expanded_rows, scenario.len());
+        let empty_cols = prefix(expanded_cols, scenario[0].len());
+
+        let galaxies = find_galaxies(scenario)
+            .into_iter()
+            .map(|(row, col)| {
+                // there are at most `row` empty rows before `row`, so this cannot underflow
+                let row = row as u128 - empty_rows[row] + empty_rows[row] * row_expansion;
+                let col = col as u128 - empty_cols[col] + empty_cols[col] * col_expansion;
+                (row, col)
+            })
+            .collect();
+        Self(galaxies)
+    }
+
+    fn distance(&self, a: usize, b: usize) -> u128 {
+        let (g1, g2) = (self.0[a], self.0[b]);
+        g1.0.abs_diff(g2.0) + g1.1.abs_diff(g2.1)
+    }
+
+    /// Sum of the distances between every pair of galaxies in O(n log n).
+    /// Manhattan distances split by axis: once sorted, coordinate `i` is
+    /// greater than or equal to the `i` previous ones.
+    fn total_distance(&self) -> u128 {
+        let axis_sum = |mut coords: Vec<u128>| {
+            coords.sort_unstable();
+            let (mut res, mut prefix) = (0, 0);
+            for (i, c) in coords.into_iter().enumerate() {
+                res += c * i as u128 - prefix;
+                prefix += c;
             }
-            res += distance;
-        }
+            res
+        };
+        axis_sum(self.0.iter().map(|g| g.0).collect())
+            + axis_sum(self.0.iter().map(|g| g.1).collect())
+    }
+
+    /// Distance between every pair of galaxies
+    fn distance_matrix(&self) -> Vec<Vec<u128>> {
+        (0..self.0.len())
+            .map(|a| (0..self.0.len()).map(|b| self.distance(a, b)).collect())
+            .collect()
+    }
+
+    /// The `k` closest galaxies (index and distance) to each galaxy, closest first
+    fn k_nearest(&self, k: usize) -> Vec<Vec<(usize, u128)>> {
+        (0..self.0.len())
+            .map(|a| {
+                let mut others = (0..self.0.len())
+                    .filter(|&b| b != a)
+                    .map(|b| (b, self.distance(a, b)))
+                    .collect::<Vec<_>>();
+                others.sort_unstable_by_key(|&(b, d)| (d, b));
+                others.truncate(k);
+                others
+            })
+            .collect()
     }
-    res
 }
 
 fn main() {
+    // `11_cosmic [--matrix]` also checks part 1 against the full distance matrix
+    let matrix_check = std::env::args().skip(1).any(|arg| arg == "--matrix");
     let scenario = parse_input("data/11_input.txt");
     let (expanded_rows, expanded_cols) = expanded_space(&scenario);
-    println!(
-        "exercise1: {}",
-        exercise1(&scenario, &expanded_rows, &expanded_cols, 2)
-    );
-    println!(
-        "exercise2: {}",
-        exercise1(&scenario, &expanded_rows, &expanded_cols, 1_000_000)
+
+    let galaxies = Galaxies::new(&scenario, &expanded_rows, &expanded_cols, 2, 2);
+    println!("exercise1: {}", galaxies.total_distance());
+    if matrix_check {
+        let matrix = galaxies.distance_matrix();
+        println!(
+            "    (matrix check: {})",
+            matrix.iter().flatten().sum::<u128>() / 2
+        );
+    }
+
+    let galaxies = Galaxies::new(
+        &scenario,
+        &expanded_rows,
+        &expanded_cols,
+        1_000_000,
+        1_000_000,
     );
+    println!("exercise2: {}", galaxies.total_distance());
+    println!("    3 nearest to galaxy 0: {:?}", galaxies.k_nearest(3)[0]);
 }
@@ -0,0 +1,150 @@
+use aoc_2023::springs::{Spring, SpringRow};
+use std::fs;
+
+fn parse_clues(path: &str) -> Vec<Vec<usize>> {
+    let file = fs::read_to_string(path).expect("cannot read file");
+    file.lines()
+        .map(|line| {
+            line.split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse().expect("invalid clue"))
+                .filter(|&n| n > 0)
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Grid(Vec<Vec<Spring>>);
+
+impl std::fmt::Display for Grid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in self.0.iter() {
+            for cell in line.iter() {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Solution {
+    Unique(Grid),
+    Multiple(Grid, Grid),
+    None,
+}
+
+struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    cols: Vec<Vec<usize>>,
+}
+
+impl Nonogram {
+    fn new(rows: Vec<Vec<usize>>, cols: Vec<Vec<usize>>) -> Self {
+        Self { rows, cols }
+    }
+
+    /// Applies line solving to every row and column until nothing changes.
+    /// Returns `false` if some line cannot be completed.
+    fn propagate(&self, grid: &mut Grid) -> bool {
+        let (n_rows, n_cols) = (self.rows.len(), self.cols.len());
+        let (mut dirty_rows, mut dirty_cols) = (vec![true; n_rows], vec![true; n_cols]);
+
+        while dirty_rows.iter().chain(dirty_cols.iter()).any(|&d| d) {
+            for (row, clues) in self.rows.iter().enumerate() {
+                if !std::mem::take(&mut dirty_rows[row]) {
+                    continue;
+                }
+                let line = SpringRow {
+                    springs: grid.0[row].clone(),
+                    groups: clues.clone(),
+                };
+                let Some(solved) = line.solve_line() else {
+                    return false;
+                };
+                for (col, cell) in solved.into_iter().enumerate() {
+                    if grid.0[row][col] != cell {
+                        grid.0[row][col] = cell;
+                        dirty_cols[col] = true;
+                    }
+                }
+            }
+            for (col, clues) in self.cols.iter().enumerate() {
+                if !std::mem::take(&mut dirty_cols[col]) {
+                    continue;
+                }
+                let line = SpringRow {
+                    springs: grid.0.iter().map(|line| line[col]).collect(),
+                    groups: clues.clone(),
+                };
+                let Some(solved) = line.solve_line() else {
+                    return false;
+                };
+                for (row, cell) in solved.into_iter().enumerate() {
+                    if grid.0[row][col] != cell {
+                        grid.0[row][col] = cell;
+                        dirty_rows[row] = true;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Backtracking on the first unknown cell. Stops after finding two solutions.
+    fn search(&self, mut grid: Grid, found: &mut Vec<Grid>) {
+        if found.len() >= 2 || !self.propagate(&mut grid) {
+            return;
+        }
+        let unknown = grid.0.iter().enumerate().find_map(|(row, line)| {
+            let col = line.iter().position(|&cell| cell == Spring::Unknown)?;
+            Some((row, col))
+        });
+        match unknown {
+            None => found.push(grid),
+            Some((row, col)) => {
+                for guess in [Spring::Damaged, Spring::Operational] {
+                    let mut candidate = grid.clone();
+                    candidate.0[row][col] = guess;
+                    self.search(candidate, found);
+                }
+            }
+        }
+    }
+
+    fn solve(&self) -> Solution {
+        let grid = Grid(vec![
+            vec![Spring::Unknown; self.cols.len()];
+            self.rows.len()
+        ]);
+        let mut found = Vec::new();
+        self.search(grid, &mut found);
+
+        let mut found = found.into_iter();
+        match (found.next(), found.next()) {
+            (None, _) => Solution::None,
+            (Some(grid), None) => Solution::Unique(grid),
+            (Some(a), Some(b)) => Solution::Multiple(a, b),
+        }
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let rows_path = args
+        .next()
+        .unwrap_or("data/12_nonogram_rows.txt".to_string());
+    let cols_path = args
+        .next()
+        .unwrap_or("data/12_nonogram_cols.txt".to_string());
+
+    let nonogram = Nonogram::new(parse_clues(&rows_path), parse_clues(&cols_path));
+    match nonogram.solve() {
+        Solution::Unique(grid) => println!("unique solution:\n{}", grid),
+        Solution::Multiple(a, b) => println!("multiple solutions, e.g.:\n{}\n{}", a, b),
+        Solution::None => println!("no solution"),
+    }
+}
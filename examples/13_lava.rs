@@ -1,130 +1,187 @@
 use std::fs;
 
-fn parse_input(path: &str) -> Vec<Vec<Vec<char>>> {
+/// A row or column of a pattern as a bitmask (`#` is 1, `.` is 0).
+/// Lines of up to 128 cells fit in a single integer, wider lines use 64-bit words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Line {
+    Narrow(u128),
+    Wide(Vec<u64>),
+}
+
+impl Line {
+    fn new(cells: &[bool]) -> Self {
+        if cells.len() <= 128 {
+            let mut bits = 0;
+            for (i, &cell) in cells.iter().enumerate() {
+                bits |= (cell as u128) << i;
+            }
+            Self::Narrow(bits)
+        } else {
+            let mut words = vec![0; cells.len().div_ceil(64)];
+            for (i, &cell) in cells.iter().enumerate() {
+                words[i / 64] |= (cell as u64) << (i % 64);
+            }
+            Self::Wide(words)
+        }
+    }
+
+    /// Number of cells that differ between two lines of the same length
+    fn count_diff(&self, other: &Self) -> usize {
+        match (self, other) {
+            (Self::Narrow(a), Self::Narrow(b)) => (a ^ b).count_ones() as usize,
+            (Self::Wide(a), Self::Wide(b)) => a
+                .iter()
+                .zip(b.iter())
+                .map(|(a, b)| (a ^ b).count_ones() as usize)
+                .sum(),
+            _ => panic!("lines of different length"),
+        }
+    }
+
+    /// Positions of the cells that differ between two lines of the same length
+    fn diff_positions(&self, other: &Self) -> Vec<usize> {
+        let mut res = Vec::new();
+        match (self, other) {
+            (Self::Narrow(a), Self::Narrow(b)) => {
+                let mut diff = a ^ b;
+                while diff != 0 {
+                    res.push(diff.trailing_zeros() as usize);
+                    diff &= diff - 1;
+                }
+            }
+            (Self::Wide(a), Self::Wide(b)) => {
+                for (word, (a, b)) in a.iter().zip(b.iter()).enumerate() {
+                    let mut diff = a ^ b;
+                    while diff != 0 {
+                        res.push(64 * word + diff.trailing_zeros() as usize);
+                        diff &= diff - 1;
+                    }
+                }
+            }
+            _ => panic!("lines of different length"),
+        }
+        res
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    rows: Vec<Line>,
+    cols: Vec<Line>,
+}
+
+impl From<&[Vec<char>]> for Pattern {
+    fn from(input: &[Vec<char>]) -> Self {
+        let cells = input
+            .iter()
+            .map(|line| line.iter().map(|&c| c == '#').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let rows = cells.iter().map(|line| Line::new(line)).collect();
+        let cols = (0..cells[0].len())
+            .map(|j| Line::new(&cells.iter().map(|line| line[j]).collect::<Vec<_>>()))
+            .collect();
+        Self { rows, cols }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    /// Mirror between two rows
+    Horizontal,
+    /// Mirror between two columns
+    Vertical,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Reflection {
+    axis: Axis,
+    /// Number of rows above (or columns left of) the mirror
+    position: usize,
+    /// `(row, col)` of the cells to flip so the reflection becomes exact
+    smudges: Vec<(usize, usize)>,
+}
+
+impl Reflection {
+    fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => 100 * self.position,
+            Axis::Vertical => self.position,
+        }
+    }
+}
+
+fn parse_input(path: &str) -> Vec<Pattern> {
     let mut res = Vec::new();
     let file = fs::read_to_string(path).expect("cannot read file");
     let lines = file.lines();
-    let mut scenario = Vec::new();
+    let mut scenario: Vec<Vec<char>> = Vec::new();
     for line in lines {
         if line.is_empty() {
-            res.push(scenario);
+            res.push(scenario.as_slice().into());
             scenario = Vec::new();
         } else {
             scenario.push(line.chars().collect());
         }
     }
-    res.push(scenario);
+    res.push(scenario.as_slice().into());
     res
 }
 
-fn exercise1(scenario: &[Vec<Vec<char>>]) -> usize {
-    let mut res = 0;
-    for input in scenario {
-        // find horizontal pattern
-        let mut i = 1;
-        while i < input.len() {
-            let mut matches = 0;
-            let max_offset = std::cmp::min(i, input.len() - i);
+/// Finds every mirror (in both axes) that requires exactly `smudges` cells to be flipped
+fn find_reflections(pattern: &Pattern, smudges: usize) -> Vec<Reflection> {
+    let mut res = Vec::new();
+    for (axis, lines) in [
+        (Axis::Horizontal, &pattern.rows),
+        (Axis::Vertical, &pattern.cols),
+    ] {
+        for i in 1..lines.len() {
+            let max_offset = std::cmp::min(i, lines.len() - i);
+            let mut diff = 0;
             for offset in 0..max_offset {
-                if input[i - 1 - offset] == input[i + offset] {
-                    matches += 1;
+                diff += lines[i - 1 - offset].count_diff(&lines[i + offset]);
+                if diff > smudges {
+                    break;
                 }
             }
-            if matches == max_offset {
-                println!("found horizontal pattern at row {}", i);
-                res += 100 * i;
+            if diff != smudges {
+                continue;
             }
-            i += 1;
-        }
-        // find vertical pattern
-        let mut i = 1;
-        while i < input[0].len() {
-            let mut matches = 0;
-            let max_offset = std::cmp::min(i, input[0].len() - i);
+            let mut cells = Vec::new();
             for offset in 0..max_offset {
-                let mut match_line = true;
-                for line in input {
-                    if line[i - 1 - offset] != line[i + offset] {
-                        match_line = false;
-                        break;
-                    }
+                let line = i - 1 - offset;
+                for pos in lines[line].diff_positions(&lines[i + offset]) {
+                    cells.push(match axis {
+                        Axis::Horizontal => (line, pos),
+                        Axis::Vertical => (pos, line),
+                    });
                 }
-                if match_line {
-                    matches += 1;
-                }
-            }
-            if matches == max_offset {
-                println!("found vertical pattern at column {}", i);
-                res += i;
             }
-            i += 1;
+            res.push(Reflection {
+                axis,
+                position: i,
+                smudges: cells,
+            });
         }
     }
     res
 }
 
-fn exercise2(scenario: &[Vec<Vec<char>>]) -> usize {
+fn exercise(patterns: &[Pattern], smudges: usize) -> usize {
     let mut res = 0;
-    for input in scenario {
-        // find horizontal pattern
-        let mut i = 1;
-        let mut found = false;
-        while i < input.len() {
-            let mut diff = 0;
-            let max_offset = std::cmp::min(i, input.len() - i);
-            for offset in 0..max_offset {
-                let a = &input[i - 1 - offset];
-                let b = &input[i + offset];
-                for j in 0..a.len() {
-                    if a[j] != b[j] {
-                        diff += 1;
-                        if diff > 1 {
-                            break;
-                        }
-                    }
-                }
-            }
-            if diff == 1 {
-                println!("found horizontal pattern at row {}", i);
-                res += 100 * i;
-                found = true;
-                break;
-            }
-            i += 1;
-        }
-        if found {
-            continue;
-        }
-        // find vertical pattern
-        let mut i = 1;
-        while i < input[0].len() {
-            let mut diff = 0;
-            let max_offset = std::cmp::min(i, input[0].len() - i);
-            for offset in 0..max_offset {
-                for line in input {
-                    if line[i - 1 - offset] != line[i + offset] {
-                        diff += 1;
-                        if diff > 1 {
-                            break;
-                        }
-                    }
-                }
-            }
-            if diff == 1 {
-                println!("found vertical pattern at column {}", i);
-                res += i;
-                found = true;
-                break;
-            }
-            i += 1;
+    for pattern in patterns {
+        for reflection in find_reflections(pattern, smudges) {
+            println!(
+                "found {:?} pattern at {} (smudges: {:?})",
+                reflection.axis, reflection.position, reflection.smudges
+            );
+            res += reflection.summary();
         }
-        assert!(found);
     }
     res
 }
 
 fn main() {
     let input = parse_input("data/13_input.txt");
-    println!("exercise 1: {}", exercise1(&input));
-    println!("exercise 2: {}", exercise2(&input));
+    println!("exercise 1: {}", exercise(&input, 0));
+    println!("exercise 2: {}", exercise(&input, 1));
 }
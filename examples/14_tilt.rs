@@ -1,153 +1,243 @@
+use std::str::FromStr;
 use std::{collections::HashMap, fs};
 
-fn parse_input(path: &str) -> Vec<Vec<char>> {
-    let file = fs::read_to_string(path).expect("cannot read file");
-    file.lines().map(|line| line.chars().collect()).collect()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    North,
+    West,
+    South,
+    East,
 }
 
-fn tilt_north(input: &[Vec<char>]) -> Vec<Vec<char>> {
-    let mut res = input.to_vec();
-    let mut ceiling = HashMap::new();
-    for (i, line) in input.iter().enumerate() {
-        for (j, c) in line.iter().enumerate() {
-            if *c == 'O' {
-                let new_pos = match ceiling.get(&j) {
-                    Some(&pos) => pos + 1,
-                    None => 0,
-                };
-                res[i][j] = '.';
-                res[new_pos][j] = 'O';
-                ceiling.insert(j, new_pos);
-            } else if *c == '#' {
-                ceiling.insert(j, i);
+/// Mask with the `n` lowest bits set
+fn ones(n: u32) -> u128 {
+    u128::MAX.checked_shr(128 - n).unwrap_or(0)
+}
+
+/// Stretch of a row or column between two cube rocks (or the platform edges)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    mask: u128,
+    low: u32,
+    high: u32,
+}
+
+fn segments(cubes: u128, len: usize) -> Vec<Segment> {
+    let mut res = Vec::new();
+    let mut low = 0;
+    for i in 0..=len as u32 {
+        if i == len as u32 || cubes & (1 << i) != 0 {
+            if i > low {
+                let mask = ones(i - low) << low;
+                res.push(Segment {
+                    mask,
+                    low,
+                    high: i - 1,
+                });
             }
+            low = i + 1;
         }
     }
     res
 }
 
-fn tilt_west(input: &[Vec<char>]) -> Vec<Vec<char>> {
-    let mut res = input.to_vec();
-    let mut ceiling = HashMap::new();
-    for (i, line) in input.iter().enumerate() {
-        for (j, c) in line.iter().enumerate() {
-            if *c == 'O' {
-                let new_pos = match ceiling.get(&i) {
-                    Some(&pos) => pos + 1,
-                    None => 0,
-                };
-                res[i][j] = '.';
-                res[i][new_pos] = 'O';
-                ceiling.insert(i, new_pos);
-            } else if *c == '#' {
-                ceiling.insert(i, j);
-            }
+/// Moves every rounded rock of a line to the low (or high) end of its segment
+fn tilt_line(rounded: u128, segments: &[Segment], to_low: bool) -> u128 {
+    let mut res = 0;
+    for segment in segments {
+        let count = (rounded & segment.mask).count_ones();
+        res |= match to_low {
+            true => ones(count) << segment.low,
+            false => ones(count) << (segment.high + 1 - count),
+        };
+    }
+    res
+}
+
+fn transpose(lines: &[u128], len: usize) -> Vec<u128> {
+    let mut res = vec![0; len];
+    for (i, &line) in lines.iter().enumerate() {
+        let mut bits = line;
+        while bits != 0 {
+            res[bits.trailing_zeros() as usize] |= 1 << i;
+            bits &= bits - 1;
         }
     }
     res
 }
 
-fn tilt_south(input: &[Vec<char>]) -> Vec<Vec<char>> {
-    let mut res = input.to_vec();
-    let mut ceiling = HashMap::new();
-    let n_rows: usize = input.len();
-    for (i, line) in input.iter().enumerate().rev() {
-        for (j, c) in line.iter().enumerate() {
-            if *c == 'O' {
-                let new_pos = match ceiling.get(&j) {
-                    Some(&pos) => pos - 1,
-                    None => n_rows - 1,
-                };
-                res[i][j] = '.';
-                res[new_pos][j] = 'O';
-                ceiling.insert(j, new_pos);
-            } else if *c == '#' {
-                ceiling.insert(j, i);
+/// Platform stored as bitsets: bit `col` of `rows[row]` and bit `row` of `cols[col]`
+/// both represent the tile at `(row, col)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Platform {
+    n_rows: usize,
+    n_cols: usize,
+    rounded_rows: Vec<u128>,
+    rounded_cols: Vec<u128>,
+    cube_rows: Vec<u128>,
+    row_segments: Vec<Vec<Segment>>,
+    col_segments: Vec<Vec<Segment>>,
+}
+
+impl FromStr for Platform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mut rounded_rows, mut cube_rows) = (Vec::new(), Vec::new());
+        let n_cols = s.lines().next().map_or(0, |line| line.chars().count());
+        if n_cols > 128 {
+            return Err("platform wider than 128 tiles".to_string());
+        }
+        for (i, line) in s.lines().enumerate() {
+            let (mut rounded, mut cubes) = (0u128, 0u128);
+            let len = line.chars().count();
+            if len != n_cols {
+                return Err(format!(
+                    "row {} has {} tiles, expected {}",
+                    i + 1,
+                    len,
+                    n_cols
+                ));
             }
+            for (j, c) in line.chars().enumerate() {
+                match c {
+                    'O' => rounded |= 1 << j,
+                    '#' => cubes |= 1 << j,
+                    '.' => {}
+                    _ => return Err(format!("invalid tile '{}'", c)),
+                }
+            }
+            rounded_rows.push(rounded);
+            cube_rows.push(cubes);
+        }
+        let n_rows = rounded_rows.len();
+        if n_rows > 128 {
+            return Err("platform taller than 128 tiles".to_string());
         }
+        let cube_cols = transpose(&cube_rows, n_cols);
+        let row_segments = cube_rows.iter().map(|&c| segments(c, n_cols)).collect();
+        let col_segments = cube_cols.iter().map(|&c| segments(c, n_rows)).collect();
+        Ok(Self {
+            n_rows,
+            n_cols,
+            rounded_cols: transpose(&rounded_rows, n_cols),
+            rounded_rows,
+            cube_rows,
+            row_segments,
+            col_segments,
+        })
     }
-    res
 }
 
-fn tilt_east(input: &[Vec<char>]) -> Vec<Vec<char>> {
-    let mut res = input.to_vec();
-    let mut ceiling = HashMap::new();
-    let n_cols: usize = input[0].len();
-    for (i, line) in input.iter().enumerate() {
-        for (j, c) in line.iter().enumerate().rev() {
-            if *c == 'O' {
-                let new_pos = match ceiling.get(&i) {
-                    Some(&pos) => pos - 1,
-                    None => n_cols - 1,
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (&rounded, &cubes) in self.rounded_rows.iter().zip(self.cube_rows.iter()) {
+            for j in 0..self.n_cols {
+                let c = if rounded & (1 << j) != 0 {
+                    'O'
+                } else if cubes & (1 << j) != 0 {
+                    '#'
+                } else {
+                    '.'
                 };
-                res[i][j] = '.';
-                res[i][new_pos] = 'O';
-                ceiling.insert(i, new_pos);
-            } else if *c == '#' {
-                ceiling.insert(i, j);
+                write!(f, "{}", c)?;
             }
+            writeln!(f)?;
         }
+        Ok(())
     }
-    res
 }
 
-fn tilt(input: &[Vec<char>]) -> Vec<Vec<char>> {
-    let mut res = tilt_north(input);
-    res = tilt_west(&res);
-    res = tilt_south(&res);
-    tilt_east(&res)
-}
+impl Platform {
+    fn tilt(&mut self, dir: Direction) {
+        match dir {
+            Direction::North | Direction::South => {
+                let to_low = dir == Direction::North;
+                for (line, segments) in self.rounded_cols.iter_mut().zip(&self.col_segments) {
+                    *line = tilt_line(*line, segments, to_low);
+                }
+                self.rounded_rows = transpose(&self.rounded_cols, self.n_rows);
+            }
+            Direction::West | Direction::East => {
+                let to_low = dir == Direction::West;
+                for (line, segments) in self.rounded_rows.iter_mut().zip(&self.row_segments) {
+                    *line = tilt_line(*line, segments, to_low);
+                }
+                self.rounded_cols = transpose(&self.rounded_rows, self.n_cols);
+            }
+        }
+    }
 
-fn exercise1(input: &[Vec<char>]) -> usize {
-    let mut res = 0;
-    let n_cols = input[0].len();
-    for (i, line) in input.iter().enumerate() {
-        for c in line {
-            if *c == 'O' {
-                res += n_cols - i;
+    fn spin(&mut self, dirs: &[Direction]) {
+        for &dir in dirs {
+            self.tilt(dir);
+        }
+    }
+
+    /// Applies `n` spins, skipping ahead as soon as the platform repeats a previous state
+    fn spin_many(&mut self, dirs: &[Direction], n: usize) {
+        let mut cache = HashMap::new();
+        let mut history = Vec::new();
+        for i in 0..n {
+            if let Some(&start) = cache.get(&self.rounded_rows) {
+                let period = i - start;
+                let target = start + (n - i) % period;
+                self.rounded_rows = history.swap_remove(target);
+                self.rounded_cols = transpose(&self.rounded_rows, self.n_cols);
+                return;
             }
+            cache.insert(self.rounded_rows.clone(), i);
+            history.push(self.rounded_rows.clone());
+            self.spin(dirs);
         }
     }
-    res
-}
 
-fn exercise2(input: &[Vec<char>]) -> usize {
-    let mut scenario = input.to_vec();
-    let mut cache = HashMap::new();
-    let mut reverse_cache = HashMap::new();
-
-    let n_expected_shifts = 1000000000;
-
-    let mut n_shifts = 0;
-    loop {
-        if cache.contains_key(&scenario) {
-            println!("Cache hit!");
-            println!("    n_shifts: {}", n_shifts);
-            let last_n_shifts = cache.get(&scenario).unwrap();
-            println!("    last_n_shifts: {}", last_n_shifts);
-            let period = n_shifts - last_n_shifts;
-            println!("    period: {}", period);
-            let n_loops = (n_expected_shifts - n_shifts) / period;
-            println!("    n_loops: {}", n_loops);
-
-            let n_shifts_extra = (n_expected_shifts - n_shifts) % period;
-            println!("    n_shifts_extra: {}", n_shifts_extra);
-            n_shifts = last_n_shifts + n_shifts_extra;
-            break;
-        } else {
-            cache.insert(scenario.clone(), n_shifts);
-            reverse_cache.insert(n_shifts, scenario.clone());
-            scenario = tilt(&scenario);
-            n_shifts += 1;
+    /// Total load on the given edge: each rounded rock weighs its distance to the opposite edge
+    fn load(&self, edge: Direction) -> usize {
+        let (lines, len) = match edge {
+            Direction::North | Direction::South => (&self.rounded_rows, self.n_rows),
+            Direction::West | Direction::East => (&self.rounded_cols, self.n_cols),
+        };
+        let mut res = 0;
+        for (i, line) in lines.iter().enumerate() {
+            let weight = match edge {
+                Direction::North | Direction::West => len - i,
+                Direction::South | Direction::East => i + 1,
+            };
+            res += weight * line.count_ones() as usize;
         }
+        res
     }
-    exercise1(reverse_cache.get(&n_shifts).unwrap())
+}
+
+fn parse_input(path: &str) -> Platform {
+    fs::read_to_string(path)
+        .expect("cannot read file")
+        .parse()
+        .expect("cannot parse input")
+}
+
+fn exercise1(input: &Platform) -> usize {
+    let mut platform = input.clone();
+    platform.tilt(Direction::North);
+    platform.load(Direction::North)
+}
+
+fn exercise2(input: &Platform) -> usize {
+    let mut platform = input.clone();
+    let cycle = [
+        Direction::North,
+        Direction::West,
+        Direction::South,
+        Direction::East,
+    ];
+    platform.spin_many(&cycle, 1_000_000_000);
+    platform.load(Direction::North)
 }
 
 fn main() {
     let input = parse_input("data/14_input.txt");
-    let res = tilt_north(&input);
-    println!("res1: {}", exercise1(&res));
+    println!("res1: {}", exercise1(&input));
 
     println!("res2: {}", exercise2(&input));
 }
@@ -1,40 +1,174 @@
 use std::collections::HashMap;
 use std::fs;
+use std::str::FromStr;
 
-fn parse_input(path: &str) -> Vec<String> {
-    let file = fs::read_to_string(path).expect("cannot read file");
-    let line = file.lines().next().unwrap();
-    line.split(',').map(|s| s.to_string()).collect()
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Insert { label: String, focal_length: usize },
+    Remove { label: String },
 }
 
-struct Box {
-    lens: HashMap<String, usize>,
-    order: Vec<String>,
+impl FromStr for Step {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(label) = s.strip_suffix('-') {
+            return Ok(Self::Remove {
+                label: label.to_string(),
+            });
+        }
+        let (label, focal_length) = s
+            .split_once('=')
+            .ok_or(format!("invalid step '{}': expected '=' or '-'", s))?;
+        let focal_length = focal_length
+            .parse()
+            .map_err(|_| format!("invalid focal length in step '{}'", s))?;
+        Ok(Self::Insert {
+            label: label.to_string(),
+            focal_length,
+        })
+    }
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Insert {
+                label,
+                focal_length,
+            } => write!(f, "{}={}", label, focal_length),
+            Self::Remove { label } => write!(f, "{}-", label),
+        }
+    }
+}
+
+/// Steps with the text they were parsed from, which is what part 1 hashes
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InitSequence(Vec<(String, Step)>);
+
+impl FromStr for InitSequence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let steps = s
+            .trim()
+            .split(',')
+            .enumerate()
+            .map(|(i, text)| {
+                let step: Step = text.parse().map_err(|e| format!("step {}: {}", i + 1, e))?;
+                Ok::<_, String>((text.to_string(), step))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(steps))
+    }
 }
 
-impl Box {
-    fn new() -> Box {
-        Box {
-            lens: HashMap::new(),
-            order: Vec::new(),
+impl InitSequence {
+    fn replay(&self) -> Replay<'_> {
+        Replay {
+            sequence: self,
+            position: 0,
+            state: LensMap::new(),
         }
     }
+}
+
+/// Cursor over an [`InitSequence`] that keeps the boxes after the first `position` steps
+struct Replay<'a> {
+    sequence: &'a InitSequence,
+    position: usize,
+    state: LensMap<String, usize>,
+}
+
+impl Replay<'_> {
+    fn state(&self) -> &LensMap<String, usize> {
+        &self.state
+    }
 
-    fn add(&mut self, label: &str, focal_length: usize) {
-        if !self.lens.contains_key(label) {
-            self.order.push(label.to_string());
+    /// Applies the next step, if any
+    fn step(&mut self) -> Option<&Step> {
+        let (_, step) = self.sequence.0.get(self.position)?;
+        match step {
+            Step::Insert {
+                label,
+                focal_length,
+            } => {
+                self.state.insert(label.clone(), *focal_length);
+            }
+            Step::Remove { label } => {
+                self.state.remove(label);
+            }
         }
-        self.lens.insert(label.to_string(), focal_length);
+        self.position += 1;
+        Some(step)
     }
 
-    fn remove(&mut self, label: &str) {
-        if self.lens.contains_key(label) {
-            self.lens.remove(label);
-            self.order.retain(|s| s != label);
+    /// Moves to the state after the first `position` steps. Moving backwards replays
+    /// the sequence from the beginning, as removed lenses lose their slot.
+    fn seek(&mut self, position: usize) {
+        if position < self.position {
+            self.position = 0;
+            self.state = LensMap::new();
         }
+        while self.position < position && self.step().is_some() {}
+    }
+
+    /// Reverts the last applied step
+    fn undo(&mut self) {
+        self.seek(self.position.saturating_sub(1));
     }
 }
 
+impl Iterator for Replay<'_> {
+    type Item = LensMap<String, usize>;
+
+    /// Box state after each step
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step()?;
+        Some(self.state.clone())
+    }
+}
+
+/// Buckets whose contents differ between two states: `(bucket, lenses in a, lenses in b)`
+type StateDiff = Vec<(usize, Vec<(String, usize)>, Vec<(String, usize)>)>;
+
+fn diff(a: &LensMap<String, usize>, b: &LensMap<String, usize>) -> StateDiff {
+    let mut res = Vec::new();
+    for i in 0..a.n_buckets().max(b.n_buckets()) {
+        let lenses = |state: &LensMap<String, usize>| {
+            state
+                .bucket_entries(i)
+                .map(|(label, &focal_length)| (label.clone(), focal_length))
+                .collect::<Vec<_>>()
+        };
+        let (lenses_a, lenses_b) = (lenses(a), lenses(b));
+        if lenses_a != lenses_b {
+            res.push((i, lenses_a, lenses_b));
+        }
+    }
+    res
+}
+
+/// Number of steps after which the boxes of two sequences differ for the first time
+fn divergence(a: &InitSequence, b: &InitSequence) -> Option<usize> {
+    let (mut replay_a, mut replay_b) = (a.replay(), b.replay());
+    for position in 1..=a.0.len().max(b.0.len()) {
+        replay_a.step();
+        replay_b.step();
+        if !diff(replay_a.state(), replay_b.state()).is_empty() {
+            return Some(position);
+        }
+    }
+    None
+}
+
+fn parse_input(path: &str) -> InitSequence {
+    fs::read_to_string(path)
+        .expect("cannot read file")
+        .parse()
+        .expect("cannot parse input")
+}
+
 fn hash(input: &str) -> usize {
     let mut res = 0;
     for c in input.chars() {
@@ -43,54 +177,174 @@ fn hash(input: &str) -> usize {
     res
 }
 
-fn exercise_1(input: &[String]) -> usize {
-    input.iter().map(|s| hash(s)).sum()
+/// Chooses the bucket of each key of a [`LensMap`]
+trait BucketHash<K> {
+    fn n_buckets(&self) -> usize;
+
+    /// Must return a value lower than `n_buckets`
+    fn bucket(&self, key: &K) -> usize;
 }
 
-fn exercise_2(input: &[String]) -> usize {
-    let mut boxes = HashMap::new();
-    for i in 0..256 {
-        boxes.insert(i, Box::new());
+/// The puzzle's HASH algorithm, with 256 buckets
+#[derive(Debug, Clone, Copy, Default)]
+struct PuzzleHash;
+
+impl<K: AsRef<str>> BucketHash<K> for PuzzleHash {
+    fn n_buckets(&self) -> usize {
+        256
+    }
+
+    fn bucket(&self, key: &K) -> usize {
+        hash(key.as_ref())
     }
+}
 
-    for line in input {
-        // println!("After \"{}\":", line);
-        if line.contains('-') {
-            let mut parts = line.split('-');
-            let label = parts.next().unwrap();
-            let target_box = boxes.get_mut(&hash(label)).unwrap();
-            target_box.remove(label);
-        } else if line.contains('=') {
-            let mut parts = line.split('=');
-            let label = parts.next().unwrap();
-            let focal_length = parts.next().unwrap().parse::<usize>().unwrap();
-            let target_box = boxes.get_mut(&hash(label)).unwrap();
-            target_box.add(label, focal_length);
-        } else {
-            panic!("invalid input");
+/// Entries of a bucket in insertion order. Removed entries leave a hole that is
+/// compacted once holes outnumber live entries, so removals are amortised O(1).
+#[derive(Debug, Clone)]
+struct Bucket<K, V> {
+    entries: Vec<Option<(K, V)>>,
+    index: HashMap<K, usize>,
+}
+
+impl<K: std::hash::Hash + Eq + Clone, V> Bucket<K, V> {
+    fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: HashMap::new(),
         }
-        // for i in 0..256 {
-        //     let target_box = boxes.get_mut(&i).unwrap();
-        //     if target_box.lens.is_empty() {
-        //         continue;
-        //     }
-        //     print!("Box {}: ", i);
-        //     for label in &target_box.order {
-        //         print!("[{} {}] ", label, target_box.lens[label]);
-        //     }
-        //     println!();
-        // }
-        // println!();
     }
-    let mut res = 0;
-    for i in 0..256 {
-        let target_box = boxes.get_mut(&i).unwrap();
-        for (j, label) in target_box.order.iter().enumerate() {
-            let length = target_box.lens[label];
-            res += (i + 1) * (j + 1) * length
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.index.get(&key) {
+            Some(&pos) => {
+                let (_, old) = self.entries[pos].as_mut().unwrap();
+                Some(std::mem::replace(old, value))
+            }
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push(Some((key, value)));
+                None
+            }
         }
     }
-    res
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let pos = self.index.remove(key)?;
+        let (_, value) = self.entries[pos].take().unwrap();
+        if 2 * self.index.len() < self.entries.len() {
+            self.entries.retain(|entry| entry.is_some());
+            for (pos, (key, _)) in self.entries.iter().flatten().enumerate() {
+                *self.index.get_mut(key).unwrap() = pos;
+            }
+        }
+        Some(value)
+    }
+
+    fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key, value))
+    }
+}
+
+/// Map split in buckets, where each bucket keeps its entries in insertion order.
+/// Replacing a value keeps the original position of its key.
+#[derive(Debug, Clone)]
+struct LensMap<K, V, H = PuzzleHash> {
+    hasher: H,
+    buckets: Vec<Bucket<K, V>>,
+}
+
+impl<K: std::hash::Hash + Eq + Clone, V, H: BucketHash<K> + Default> LensMap<K, V, H> {
+    fn new() -> Self {
+        Self::with_hasher(H::default())
+    }
+}
+
+impl<K: std::hash::Hash + Eq + Clone, V, H: BucketHash<K>> LensMap<K, V, H> {
+    fn with_hasher(hasher: H) -> Self {
+        let buckets = (0..hasher.n_buckets()).map(|_| Bucket::new()).collect();
+        Self { hasher, buckets }
+    }
+
+    /// Inserts or replaces a value, returning the previous one
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let bucket = self.hasher.bucket(&key);
+        self.buckets[bucket].insert(key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let bucket = self.hasher.bucket(key);
+        self.buckets[bucket].remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.buckets.iter().map(|b| b.len()).sum()
+    }
+
+    fn n_buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Entries of the given bucket, in order
+    fn bucket_entries(&self, bucket: usize) -> impl Iterator<Item = (&K, &V)> {
+        self.buckets.get(bucket).into_iter().flat_map(|b| b.iter())
+    }
+
+    /// Iterates in puzzle order: `(bucket, slot, key, value)`, both indices starting at 0
+    fn iter(&self) -> impl Iterator<Item = (usize, usize, &K, &V)> {
+        self.buckets.iter().enumerate().flat_map(|(i, bucket)| {
+            bucket
+                .iter()
+                .enumerate()
+                .map(move |(j, (key, value))| (i, j, key, value))
+        })
+    }
+
+    fn focusing_power(&self) -> usize
+    where
+        V: Copy + Into<usize>,
+    {
+        self.iter()
+            .map(|(i, j, _, &value)| (i + 1) * (j + 1) * value.into())
+            .sum()
+    }
+}
+
+impl<K: std::fmt::Display, V: std::fmt::Display, H> std::fmt::Display for LensMap<K, V, H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            let mut entries = bucket.entries.iter().flatten().peekable();
+            if entries.peek().is_none() {
+                continue;
+            }
+            write!(f, "Box {}:", i)?;
+            for (key, value) in entries {
+                write!(f, " [{} {}]", key, value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn exercise_1(input: &InitSequence) -> usize {
+    input.0.iter().map(|(text, _)| hash(text)).sum()
+}
+
+fn exercise_2(input: &InitSequence) -> usize {
+    let mut replay = input.replay();
+    replay.seek(input.0.len());
+    let boxes = replay.state();
+    println!("{} lenses left:", boxes.len());
+    print!("{}", boxes);
+    boxes.focusing_power()
 }
 
 fn main() {
@@ -98,4 +352,22 @@ fn main() {
 
     println!("Exercise 1: {}", exercise_1(&input));
     println!("Exercise 2: {}", exercise_2(&input));
+
+    // the replay demo needs at least 11 steps
+    if input.0.len() <= 10 {
+        return;
+    }
+    let mut replay = input.replay();
+    replay.seek(10);
+    let before = replay.state().clone();
+    replay.undo();
+    println!("After step 9:\n{}", replay.state());
+    println!("Step 10 changes: {:?}", diff(replay.state(), &before));
+
+    let mut altered = input.clone();
+    altered.0.remove(10);
+    println!(
+        "Divergence without step 11: {:?}",
+        divergence(&input, &altered)
+    );
 }
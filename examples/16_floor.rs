@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::fs;
 
 fn parse_input(path: &str) -> Vec<Vec<char>> {
@@ -6,18 +6,26 @@ fn parse_input(path: &str) -> Vec<Vec<char>> {
     file.lines().map(|line| line.chars().collect()).collect()
 }
 
-fn exercise_1(input: &[Vec<char>], initial_pos: (i32, i32), initial_dir: (i32, i32)) -> usize {
-    let mut cache = HashSet::new();
-    let mut energized = HashSet::new();
+/// Tiles energized by a beam entering at `initial_pos` towards `initial_dir`
+fn trace(input: &[Vec<char>], initial_pos: (i32, i32), initial_dir: (i32, i32)) -> Vec<(i32, i32)> {
+    let (n_rows, n_cols) = (input.len() as i32, input[0].len() as i32);
+    // one bit per direction already traced through each tile
+    let mut cache = vec![vec![0u8; n_cols as usize]; n_rows as usize];
+    let mut energized = Vec::new();
     let mut beams = vec![(initial_pos, initial_dir)];
     while let Some((mut pos, mut dir)) = beams.pop() {
-        if pos.0 < 0 || pos.0 >= input.len() as i32 || pos.1 < 0 || pos.1 >= input[0].len() as i32 {
+        if pos.0 < 0 || pos.0 >= n_rows || pos.1 < 0 || pos.1 >= n_cols {
             continue;
         }
-        if !cache.insert((pos, dir)) {
+        let seen = &mut cache[pos.0 as usize][pos.1 as usize];
+        let bit = 1 << ((dir.0 + 1) * 2 + dir.1 + 1);
+        if *seen & bit != 0 {
             continue;
         }
-        energized.insert(pos);
+        if *seen == 0 {
+            energized.push(pos);
+        }
+        *seen |= bit;
 
         let c = input[pos.0 as usize][pos.1 as usize];
         match c {
@@ -56,51 +64,311 @@ fn exercise_1(input: &[Vec<char>], initial_pos: (i32, i32), initial_dir: (i32, i
                 beams.push((pos, dir));
             }
         }
+    }
+    energized
+}
 
-        // for i in 0..input.len() {
-        //     for j in 0..input[0].len() {
-        //         if energized.contains(&(i as i32, j as i32)) {
-        //             print!("X");
-        //         } else {
-        //             print!(".");
-        //         }
-        //     }
-        //     println!();
-        // }
-        // println!();
-    }
-    energized.len()
+fn exercise_1(input: &[Vec<char>], initial_pos: (i32, i32), initial_dir: (i32, i32)) -> usize {
+    trace(input, initial_pos, initial_dir).len()
 }
 
-fn exercise_2(input: &[Vec<char>]) -> usize {
+type Pos = (i32, i32);
+type Dir = (i32, i32);
+
+/// Directions leaving a tile hit by a beam, or `None` if the beam goes straight through
+fn deflect(c: char, dir: Dir) -> Option<Vec<Dir>> {
+    match c {
+        '/' => Some(vec![(-dir.1, -dir.0)]),
+        '\\' => Some(vec![(dir.1, dir.0)]),
+        '-' if dir.0 != 0 => Some(vec![(0, -1), (0, 1)]),
+        '|' if dir.1 != 0 => Some(vec![(-1, 0), (1, 0)]),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bitset(Vec<u64>);
+
+impl Bitset {
+    fn new(len: usize) -> Self {
+        Self(vec![0; len.div_ceil(64)])
+    }
+
+    fn insert(&mut self, i: usize) {
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a |= b;
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+/// The contraption compiled into straight beam segments. A segment starts at a tile with a
+/// direction and ends at the first tile that deflects or splits the beam (or at the border).
+struct SegmentGraph {
+    index: HashMap<(Pos, Dir), usize>,
+    starts: Vec<(Pos, Dir)>,
+    tiles: Vec<Bitset>,
+    next: Vec<Vec<usize>>,
+}
+
+impl SegmentGraph {
+    fn new(input: &[Vec<char>]) -> Self {
+        let mut graph = Self {
+            index: HashMap::new(),
+            starts: Vec::new(),
+            tiles: Vec::new(),
+            next: Vec::new(),
+        };
+        let mut pending = Vec::new();
+        for (pos, dir) in entries(input) {
+            graph.node(pos, dir, &mut pending);
+        }
+        while let Some(id) = pending.pop() {
+            graph.build(input, id, &mut pending);
+        }
+        graph
+    }
+
+    /// Identifier of the segment starting at `pos` towards `dir`, creating it if needed
+    fn node(&mut self, pos: Pos, dir: Dir, pending: &mut Vec<usize>) -> usize {
+        if let Some(&id) = self.index.get(&(pos, dir)) {
+            return id;
+        }
+        let id = self.tiles.len();
+        self.index.insert((pos, dir), id);
+        self.starts.push((pos, dir));
+        self.tiles.push(Bitset::new(0));
+        self.next.push(Vec::new());
+        pending.push(id);
+        id
+    }
+
+    fn build(&mut self, input: &[Vec<char>], id: usize, pending: &mut Vec<usize>) {
+        let (n_rows, n_cols) = (input.len() as i32, input[0].len() as i32);
+        let (mut pos, dir) = self.starts[id];
+        let mut tiles = Bitset::new((n_rows * n_cols) as usize);
+        let mut next = Vec::new();
+        while pos.0 >= 0 && pos.0 < n_rows && pos.1 >= 0 && pos.1 < n_cols {
+            tiles.insert((pos.0 * n_cols + pos.1) as usize);
+            if let Some(dirs) = deflect(input[pos.0 as usize][pos.1 as usize], dir) {
+                for new_dir in dirs {
+                    let new_pos = (pos.0 + new_dir.0, pos.1 + new_dir.1);
+                    if new_pos.0 >= 0 && new_pos.0 < n_rows && new_pos.1 >= 0 && new_pos.1 < n_cols
+                    {
+                        next.push(self.node(new_pos, new_dir, pending));
+                    }
+                }
+                break;
+            }
+            pos = (pos.0 + dir.0, pos.1 + dir.1);
+        }
+        self.tiles[id] = tiles;
+        self.next[id] = next;
+    }
+
+    /// Strongly connected components (Tarjan), successors before predecessors
+    fn components(&self) -> Vec<Vec<usize>> {
+        let n = self.tiles.len();
+        let (mut index, mut low) = (vec![usize::MAX; n], vec![0; n]);
+        let (mut on_stack, mut stack) = (vec![false; n], Vec::new());
+        let mut res = Vec::new();
+        let mut counter = 0;
+
+        for root in 0..n {
+            if index[root] != usize::MAX {
+                continue;
+            }
+            let mut calls = vec![(root, 0)];
+            while let Some((node, child)) = calls.pop() {
+                if child == 0 {
+                    index[node] = counter;
+                    low[node] = counter;
+                    counter += 1;
+                    stack.push(node);
+                    on_stack[node] = true;
+                }
+                if let Some(&succ) = self.next[node].get(child) {
+                    calls.push((node, child + 1));
+                    if index[succ] == usize::MAX {
+                        calls.push((succ, 0));
+                    } else if on_stack[succ] {
+                        low[node] = low[node].min(index[succ]);
+                    }
+                    continue;
+                }
+                if let Some(&(parent, _)) = calls.last() {
+                    low[parent] = low[parent].min(low[node]);
+                }
+                if low[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    res.push(component);
+                }
+            }
+        }
+        res
+    }
+
+    /// Number of energized tiles when the beam enters through each segment
+    fn energized(&self) -> Vec<usize> {
+        let mut component_of = vec![0; self.tiles.len()];
+        let mut energized: Vec<Bitset> = Vec::new();
+        for (c, component) in self.components().into_iter().enumerate() {
+            for &node in component.iter() {
+                component_of[node] = c;
+            }
+            let mut tiles = self.tiles[component[0]].clone();
+            for &node in component.iter() {
+                tiles.union_with(&self.tiles[node]);
+                for &succ in self.next[node].iter() {
+                    if component_of[succ] != c {
+                        tiles.union_with(&energized[component_of[succ]]);
+                    }
+                }
+            }
+            energized.push(tiles);
+        }
+        component_of.iter().map(|&c| energized[c].count()).collect()
+    }
+}
+
+/// Every way of entering the contraption from its border
+fn entries(input: &[Vec<char>]) -> Vec<(Pos, Dir)> {
     let n_rows = input.len() as i32;
     let n_cols = input[0].len() as i32;
-    let mut best = 0;
-
+    let mut res = Vec::new();
     for i in 0..n_rows {
-        let ex_1 = exercise_1(input, (i, 0), (0, 1));
-        let ex_2 = exercise_1(input, (i, n_cols - 1), (0, -1));
-        let ex = ex_1.max(ex_2);
-        if ex > best {
-            best = ex;
-            println!("starting at {:?} {:?}: {}", (i, 0), (0, 1), best)
-        }
+        res.push(((i, 0), (0, 1)));
+        res.push(((i, n_cols - 1), (0, -1)));
     }
     for j in 0..n_cols {
-        let ex_1 = exercise_1(input, (0, j), (1, 0));
-        let ex_2 = exercise_1(input, (n_rows - 1, j), (-1, 0));
-        let ex = ex_1.max(ex_2);
-        if ex > best {
-            best = ex;
-            println!("starting at {:?} {:?}: {}", (0, j), (1, 0), best)
+        res.push(((0, j), (1, 0)));
+        res.push(((n_rows - 1, j), (-1, 0)));
+    }
+    res
+}
+
+/// Energized tiles for every border entry
+fn energized_table(input: &[Vec<char>]) -> Vec<(Pos, Dir, usize)> {
+    let graph = SegmentGraph::new(input);
+    let energized = graph.energized();
+    entries(input)
+        .into_iter()
+        .map(|(pos, dir)| (pos, dir, energized[graph.index[&(pos, dir)]]))
+        .collect()
+}
+
+fn exercise_2(input: &[Vec<char>]) -> usize {
+    let table = energized_table(input);
+    let best = table.iter().max_by_key(|(_, _, n)| *n).unwrap();
+    println!("starting at {:?} {:?}: {}", best.0, best.1, best.2);
+    best.2
+}
+
+/// Optical element placed on an empty tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Placement {
+    pos: Pos,
+    element: char,
+}
+
+const ELEMENTS: [char; 4] = ['/', '\\', '-', '|'];
+
+/// Exhaustive search of the single placement that energizes the most tiles.
+/// Only empty tiles already reached by the beam are candidates: elsewhere, a new
+/// element would not change anything.
+fn best_placement(grid: &[Vec<char>], entry: (Pos, Dir)) -> Option<(Placement, usize)> {
+    let mut grid = grid.to_vec();
+    let mut candidates = trace(&grid, entry.0, entry.1)
+        .into_iter()
+        .filter(|&(i, j)| grid[i as usize][j as usize] == '.')
+        .collect::<Vec<_>>();
+    candidates.sort();
+
+    let mut best: Option<(Placement, usize)> = None;
+    for pos in candidates {
+        for element in ELEMENTS {
+            grid[pos.0 as usize][pos.1 as usize] = element;
+            let energized = exercise_1(&grid, entry.0, entry.1);
+            if best.is_none_or(|(_, n)| energized > n) {
+                best = Some((Placement { pos, element }, energized));
+            }
         }
+        grid[pos.0 as usize][pos.1 as usize] = '.';
     }
     best
 }
 
+/// Places up to `k` new elements on empty tiles to maximise the energized tiles from `entry`.
+/// Exact for `k = 1`. For larger budgets, elements are placed greedily and then
+/// relocated one at a time (local search) until no move improves the result.
+fn optimise(
+    input: &[Vec<char>],
+    entry: (Pos, Dir),
+    k: usize,
+) -> (Vec<Vec<char>>, Vec<Placement>, usize) {
+    let mut grid = input.to_vec();
+    let mut placements: Vec<Placement> = Vec::new();
+    let mut best = exercise_1(&grid, entry.0, entry.1);
+
+    for _ in 0..k {
+        match best_placement(&grid, entry) {
+            Some((placement, energized)) if energized > best => {
+                grid[placement.pos.0 as usize][placement.pos.1 as usize] = placement.element;
+                placements.push(placement);
+                best = energized;
+            }
+            _ => break,
+        }
+    }
+
+    let mut improved = k > 1;
+    while improved {
+        improved = false;
+        for slot in placements.iter_mut() {
+            let old = *slot;
+            grid[old.pos.0 as usize][old.pos.1 as usize] = '.';
+            match best_placement(&grid, entry) {
+                Some((placement, energized)) if energized > best => {
+                    grid[placement.pos.0 as usize][placement.pos.1 as usize] = placement.element;
+                    *slot = placement;
+                    best = energized;
+                    improved = true;
+                }
+                _ => grid[old.pos.0 as usize][old.pos.1 as usize] = old.element,
+            }
+        }
+    }
+    (grid, placements, best)
+}
+
 fn main() {
     let input = parse_input("data/16_input.txt");
 
     println!("Exercise 1: {}", exercise_1(&input, (0, 0), (0, 1)));
     println!("Exercise 2: {}", exercise_2(&input));
+
+    let budget = std::env::args()
+        .nth(1)
+        .map(|k| k.parse().expect("invalid budget"))
+        .unwrap_or(1);
+    let (grid, placements, energized) = optimise(&input, ((0, 0), (0, 1)), budget);
+    println!("Best placement of {} elements: {:?}", budget, placements);
+    for line in grid.iter() {
+        println!("{}", line.iter().collect::<String>());
+    }
+    println!("Energized: {}", energized);
 }
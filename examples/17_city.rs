@@ -1,141 +1,125 @@
-use std::collections::{BinaryHeap, HashMap};
+use aoc_2023::search::{Dir, MovementRule, Path, Queue, Search};
+use aoc_2023::Point;
 use std::fs;
 
-fn parse_input(path: &str) -> Vec<Vec<i32>> {
+fn parse_input(path: &str) -> Vec<Vec<u32>> {
     let file = fs::read_to_string(path).expect("cannot read file");
     file.lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_digit(10).unwrap() as i32)
-                .collect()
-        })
+        .map(|line| line.chars().map(|c| c.to_digit(10).unwrap()).collect())
         .collect()
 }
 
-fn dijkstra(
-    start: (i32, i32),
-    end: (i32, i32),
-    min_step: i32,
-    max_step: i32,
-    min_turn: i32,
-    grid: &[Vec<i32>],
-) -> Option<i32> {
-    let mut dist = HashMap::new();
-    let mut heap = BinaryHeap::new();
-
-    let st1 = State {
-        point: start,
-        dir: (1, 0),
-        cost: 0,
-    };
-    let st2 = State {
-        point: start,
-        dir: (0, 1),
-        cost: 0,
-    };
-
-    dist.insert(StateKey::from(st1), 0);
-    dist.insert(StateKey::from(st2), 0);
-    heap.push(st1);
-    heap.push(st2);
-
-    while let Some(state @ State { cost, point, dir }) = heap.pop() {
-        if point == end {
-            return Some(cost);
-        }
-        if dist.get(&state.into()).is_some_and(|&c| c < cost) {
-            continue;
-        }
-
-        let mut new_cost = cost;
-        for step in min_step..=max_step {
-            let new_point = (point.0 + dir.0 * step, point.1 + dir.1 * step);
-            if new_point.0 < 0
-                || new_point.0 >= grid.len() as i32
-                || new_point.1 < 0
-                || new_point.1 >= grid[0].len() as i32
-            {
-                continue;
-            }
-            new_cost += grid[new_point.0 as usize][new_point.1 as usize];
-
-            if step < min_turn {
-                continue;
-            }
+/// Crucibles move at least `min_step` and at most `max_step` blocks before turning
+struct Crucible {
+    min_step: usize,
+    max_step: usize,
+}
 
-            for new_dir in [(-dir.1, -dir.0), (dir.1, dir.0)] {
-                let new_state = State {
-                    point: new_point,
-                    dir: new_dir,
-                    cost: new_cost,
-                };
+impl MovementRule for Crucible {
+    fn min_run(&self) -> usize {
+        self.min_step
+    }
 
-                if dist.get(&new_state.into()).is_some_and(|&c| c <= new_cost) {
-                    // For p1 and p2
-                    // Streak gets too long
-                    // Or a better solution/path already exists
-                    continue;
-                }
-                heap.push(new_state);
-                dist.insert(new_state.into(), new_state.cost);
-            }
-        }
+    fn max_run(&self) -> usize {
+        self.max_step
     }
-    None
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-struct State {
-    point: (i32, i32),
-    dir: (i32, i32),
-    cost: i32,
+/// One tile of a route: the crucible enters `point` moving towards `dir`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Step {
+    point: Point,
+    dir: Dir,
+    /// Tiles moved in a straight line so far, including this one
+    run: usize,
+    heat_loss: u32,
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.point.cmp(&other.point))
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Route {
+    start: Point,
+    steps: Vec<Step>,
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl Route {
+    fn new(path: &Path, grid: &[Vec<u32>]) -> Self {
+        let steps = path.states[1..]
+            .iter()
+            .map(|state| Step {
+                point: state.point,
+                dir: state.dir,
+                run: state.run,
+                heat_loss: grid[state.point.row as usize][state.point.col as usize],
+            })
+            .collect();
+        Self {
+            start: path.states[0].point,
+            steps,
+        }
     }
-}
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-struct StateKey {
-    point: (i32, i32),
-    dir: (i32, i32),
-}
+    fn cost(&self) -> u32 {
+        self.steps.iter().map(|s| s.heat_loss).sum()
+    }
 
-impl From<State> for StateKey {
-    fn from(value: State) -> Self {
-        Self {
-            point: value.point,
-            dir: value.dir,
+    /// Overlays the route on the grid using `>v<^`
+    fn render(&self, grid: &[Vec<u32>]) -> String {
+        let mut res = grid
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|&c| char::from_digit(c, 10).unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for step in self.steps.iter() {
+            res[step.point.row as usize][step.point.col as usize] = match step.dir {
+                Dir::East => '>',
+                Dir::South => 'v',
+                Dir::West => '<',
+                Dir::North => '^',
+            };
         }
+        res.into_iter()
+            .map(|line| line.into_iter().collect::<String>() + "\n")
+            .collect()
     }
 }
 
+fn crucible_route(grid: &[Vec<u32>], min_step: usize, max_step: usize) -> Option<Route> {
+    let origin = Point::new(0, 0);
+    let destination = Point::new(grid.len() as isize - 1, grid[0].len() as isize - 1);
+    let rule = Crucible { min_step, max_step };
+    let path = Search::new(grid, rule)
+        .queue(Queue::Buckets)
+        .run(&[origin], &[destination])?;
+    Some(Route::new(&path, grid))
+}
+
 fn main() {
-    let loss_matrix = parse_input("data/input.txt");
-    let origin = (0, 0);
-    let destination = (
-        loss_matrix.len() as i32 - 1,
-        loss_matrix[0].len() as i32 - 1,
-    );
+    let loss_matrix = parse_input("data/17_input.txt");
 
-    println!(
-        "Exercise 1: {}",
-        dijkstra(origin, destination, 1, 3, 1, &loss_matrix).unwrap()
-    );
+    let crucible = crucible_route(&loss_matrix, 1, 3).unwrap();
+    println!("{}", crucible.render(&loss_matrix));
+    println!("Exercise 1: {}", crucible.cost());
+
+    let ultra = crucible_route(&loss_matrix, 4, 10).unwrap();
+    println!("{}", ultra.render(&loss_matrix));
+    println!("Exercise 2: {}", ultra.cost());
 
+    let shared = ultra
+        .steps
+        .iter()
+        .filter(|u| crucible.steps.iter().any(|c| c.point == u.point))
+        .count();
     println!(
-        "Exercise 2: {}",
-        dijkstra(origin, destination, 1, 10, 4, &loss_matrix).unwrap()
+        "Routes from {}: {} and {} tiles long, {} tiles in common",
+        crucible.start,
+        crucible.steps.len(),
+        ultra.steps.len(),
+        shared
     );
+    for step in ultra.steps.iter().take(5) {
+        println!("    {:?}", step);
+    }
 }
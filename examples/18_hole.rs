@@ -1,3 +1,7 @@
+use aoc_2023::check::{shrink_usize, Checker};
+use aoc_2023::generate;
+use aoc_2023::rng::Rng;
+use std::collections::HashSet;
 use std::fs;
 use std::str::FromStr;
 
@@ -88,14 +92,336 @@ fn calc_filled(instructions: &[Instruction]) -> i64 {
     area + 1
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PlanError {
+    /// The last instruction does not end where the first one started
+    NotClosed { end: (i64, i64) },
+    /// Instruction `second` digs through the trench of instruction `first`
+    SelfIntersection {
+        first: usize,
+        second: usize,
+        point: (i64, i64),
+    },
+}
+
+impl std::fmt::Display for PlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotClosed { end } => write!(f, "dig plan ends at {:?}, not at the origin", end),
+            Self::SelfIntersection {
+                first,
+                second,
+                point,
+            } => write!(
+                f,
+                "instruction {} crosses instruction {} at {:?}",
+                second + 1,
+                first + 1,
+                point
+            ),
+        }
+    }
+}
+
+/// Cells shared by two axis-aligned segments, as the corners of their bounding box
+fn overlap(
+    a: ((i64, i64), (i64, i64)),
+    b: ((i64, i64), (i64, i64)),
+) -> Option<((i64, i64), (i64, i64))> {
+    let low = (
+        a.0 .0.min(a.1 .0).max(b.0 .0.min(b.1 .0)),
+        a.0 .1.min(a.1 .1).max(b.0 .1.min(b.1 .1)),
+    );
+    let high = (
+        a.0 .0.max(a.1 .0).min(b.0 .0.max(b.1 .0)),
+        a.0 .1.max(a.1 .1).min(b.0 .1.max(b.1 .1)),
+    );
+    match low.0 <= high.0 && low.1 <= high.1 {
+        true => Some((low, high)),
+        false => None,
+    }
+}
+
+/// A validated dig plan: a closed trench that does not cross itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DigPlan {
+    instructions: Vec<Instruction>,
+    path: Vec<(i64, i64)>,
+}
+
+impl DigPlan {
+    fn new(instructions: &[Instruction]) -> Result<Self, PlanError> {
+        let path = compute_path(instructions);
+        let end = *path.last().unwrap();
+        if end != path[0] {
+            return Err(PlanError::NotClosed { end });
+        }
+
+        let n = instructions.len();
+        let segment = |i: usize| (path[i], path[i + 1]);
+        for second in 0..n {
+            for first in 0..second {
+                let Some((low, high)) = overlap(segment(first), segment(second)) else {
+                    continue;
+                };
+                // consecutive instructions share a corner, but must not dig back over each other
+                let adjacent = second == first + 1 || (first == 0 && second == n - 1);
+                if !adjacent || low != high {
+                    let point = match low == segment(second).0 {
+                        true => high,
+                        false => low,
+                    };
+                    return Err(PlanError::SelfIntersection {
+                        first,
+                        second,
+                        point,
+                    });
+                }
+            }
+        }
+        Ok(Self {
+            instructions: instructions.to_vec(),
+            path,
+        })
+    }
+
+    /// Cubes dug along the trench
+    fn boundary(&self) -> i64 {
+        self.instructions.iter().map(|i| i.steps as i64).sum()
+    }
+
+    /// Cubes dug inside the trench, using Pick's theorem: `A = I + B / 2 - 1`
+    fn interior(&self) -> i64 {
+        let mut area = 0;
+        for pair in self.path.windows(2) {
+            area += pair[0].0 * pair[1].1 - pair[0].1 * pair[1].0;
+        }
+        i64::abs(area) / 2 - self.boundary() / 2 + 1
+    }
+
+    fn filled(&self) -> i64 {
+        self.interior() + self.boundary()
+    }
+
+    fn rasterise(&self) -> Raster {
+        let min_row = self.path.iter().map(|p| p.0).min().unwrap();
+        let max_row = self.path.iter().map(|p| p.0).max().unwrap();
+        let min_col = self.path.iter().map(|p| p.1).min().unwrap();
+        let max_col = self.path.iter().map(|p| p.1).max().unwrap();
+        let (n_rows, n_cols) = (
+            (max_row - min_row + 1) as usize,
+            (max_col - min_col + 1) as usize,
+        );
+
+        let mut cells = vec![vec![Cell::Outside; n_cols]; n_rows];
+        let mut crossings = vec![Vec::new(); n_rows];
+        for (pair, instruction) in self.path.windows(2).zip(self.instructions.iter()) {
+            let (from, to) = (pair[0], pair[1]);
+            let (d_row, d_col) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+            let mut pos = from;
+            while pos != to {
+                pos = (pos.0 + d_row, pos.1 + d_col);
+                let (row, col) = ((pos.0 - min_row) as usize, (pos.1 - min_col) as usize);
+                cells[row][col] = Cell::Trench(instruction.color);
+            }
+            // vertical edges cross the rows in [min, max)
+            if from.1 == to.1 {
+                for row in from.0.min(to.0)..from.0.max(to.0) {
+                    crossings[(row - min_row) as usize].push((from.1 - min_col) as usize);
+                }
+            }
+        }
+        for (line, crossings) in cells.iter_mut().zip(crossings.iter_mut()) {
+            crossings.sort_unstable();
+            for pair in crossings.chunks(2) {
+                for cell in line[pair[0]..pair[1]].iter_mut() {
+                    if *cell == Cell::Outside {
+                        *cell = Cell::Interior;
+                    }
+                }
+            }
+        }
+        Raster(cells)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Outside,
+    Interior,
+    /// Dug by an instruction with the given color
+    Trench(usize),
+}
+
+/// Lagoon rendered in Netpbm formats (plain text versions)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Raster(Vec<Vec<Cell>>);
+
+impl Raster {
+    fn header(&self, magic: &str) -> String {
+        format!("{}\n{} {}\n", magic, self.0[0].len(), self.0.len())
+    }
+
+    /// Black and white: dug cubes are black
+    fn to_pbm(&self) -> String {
+        let mut res = self.header("P1");
+        for line in self.0.iter() {
+            let bits = line.iter().map(|&c| match c {
+                Cell::Outside => "0",
+                _ => "1",
+            });
+            res += &bits.collect::<Vec<_>>().join(" ");
+            res.push('\n');
+        }
+        res
+    }
+
+    /// Grayscale: trench in black, interior in gray
+    fn to_pgm(&self) -> String {
+        let mut res = self.header("P2") + "255\n";
+        for line in self.0.iter() {
+            let levels = line.iter().map(|&c| match c {
+                Cell::Outside => "255",
+                Cell::Interior => "160",
+                Cell::Trench(_) => "0",
+            });
+            res += &levels.collect::<Vec<_>>().join(" ");
+            res.push('\n');
+        }
+        res
+    }
+
+    /// Color: trench with the color of the instruction that dug it, interior in gray
+    fn to_ppm(&self) -> String {
+        let mut res = self.header("P3") + "255\n";
+        for line in self.0.iter() {
+            let pixels = line.iter().map(|&c| {
+                let color = match c {
+                    Cell::Outside => 0xffffff,
+                    Cell::Interior => 0xa0a0a0,
+                    Cell::Trench(color) => color,
+                };
+                format!("{} {} {}", color >> 16, (color >> 8) & 0xff, color & 0xff)
+            });
+            res += &pixels.collect::<Vec<_>>().join(" ");
+            res.push('\n');
+        }
+        res
+    }
+}
+
+/// Cubes dug out, by digging the trench one cube at a time and flooding the outside of
+/// its bounding box. Coordinates are doubled, so the outside can flow between parallel
+/// runs of the trench that are next to each other without being connected.
+fn brute_force(instructions: &[Instruction]) -> i64 {
+    let mut trench = HashSet::from([(0i64, 0i64)]);
+    let mut pos = (0, 0);
+    for instruction in instructions {
+        let step = match instruction.dir {
+            'U' => (-1, 0),
+            'D' => (1, 0),
+            'L' => (0, -1),
+            _ => (0, 1),
+        };
+        for _ in 0..2 * instruction.steps {
+            pos = (pos.0 + step.0, pos.1 + step.1);
+            trench.insert(pos);
+        }
+    }
+    // one more cube around, so the outside is connected
+    let rows = trench.iter().map(|p| p.0);
+    let cols = trench.iter().map(|p| p.1);
+    let (top, bottom) = (rows.clone().min().unwrap() - 2, rows.max().unwrap() + 2);
+    let (left, right) = (cols.clone().min().unwrap() - 2, cols.max().unwrap() + 2);
+    let mut outside = HashSet::from([(top, left)]);
+    let mut pending = vec![(top, left)];
+    while let Some((row, col)) = pending.pop() {
+        for next in [
+            (row + 1, col),
+            (row - 1, col),
+            (row, col + 1),
+            (row, col - 1),
+        ] {
+            let inside_box = (top..=bottom).contains(&next.0) && (left..=right).contains(&next.1);
+            if inside_box && !trench.contains(&next) && outside.insert(next) {
+                pending.push(next);
+            }
+        }
+    }
+    let cubes = (top..=bottom).step_by(2).flat_map(|row| {
+        let cols = (left..=right).step_by(2);
+        cols.map(move |col| (row, col))
+    });
+    cubes.filter(|cube| !outside.contains(cube)).count() as i64
+}
+
+/// Compares the shoelace formula of `calc_filled` with the brute force, on generated dig
+/// plans. The inputs are `(size, seed)` of the generator, so shrinking makes the plan
+/// smaller.
+fn check(checker: &Checker) -> bool {
+    checker.check(
+        "calc_filled",
+        |rng| (rng.range(2..12), rng.next_u64()),
+        |&(size, seed)| {
+            let smaller = shrink_usize(size).into_iter().filter(|&s| s >= 2);
+            smaller.map(|s| (s, seed)).collect()
+        },
+        |&(size, seed)| {
+            let input = generate::generate(18, size, &mut Rng::new(seed))
+                .unwrap()
+                .input;
+            let instructions = input
+                .lines()
+                .map(|line| line.parse().unwrap())
+                .collect::<Vec<Instruction>>();
+            let (fast, slow) = (calc_filled(&instructions), brute_force(&instructions));
+            match fast == slow {
+                true => Ok(()),
+                false => Err(format!("shoelace {}, flood fill {}", fast, slow)),
+            }
+        },
+    )
+}
+
 fn main() {
+    // `--check [cases] [seed]` compares calc_filled with a brute force instead
+    Checker::run_from_args(check);
+
     let mut instructions = parse_input("data/18_input.txt");
-    // exercise_1(&instructions);
 
     let area = calc_filled(&instructions);
     println!("area: {}", area);
 
+    let plan = DigPlan::new(&instructions).unwrap_or_else(|e| panic!("{}", e));
+    println!(
+        "    trench: {}, interior: {}, total: {}",
+        plan.boundary(),
+        plan.interior(),
+        plan.filled()
+    );
+    let raster = plan.rasterise();
+    let dug = raster.0.iter().flatten().filter(|&&c| c != Cell::Outside);
+    println!("    rasterised cubes: {}", dug.count());
+    if let Some(path) = std::env::args().nth(1) {
+        let image = match path.rsplit('.').next() {
+            Some("pbm") => raster.to_pbm(),
+            Some("pgm") => raster.to_pgm(),
+            _ => raster.to_ppm(),
+        };
+        fs::write(&path, image).expect("cannot write image");
+    }
+
     instructions.iter_mut().for_each(|i| i.adapt());
     let area = calc_filled(&instructions);
     println!("area: {}", area);
+    match DigPlan::new(&instructions) {
+        Ok(plan) => println!(
+            "    trench: {}, interior: {}",
+            plan.boundary(),
+            plan.interior()
+        ),
+        Err(e) => println!("    invalid plan: {}", e),
+    }
 }
+
+aoc_2023::check_test!(check);
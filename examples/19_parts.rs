@@ -59,7 +59,7 @@ impl FromStr for Part {
             .trim_start_matches('{')
             .trim_end_matches('}')
             .split(',')
-            .map(|s| s.split('=').last().unwrap())
+            .map(|s| s.split('=').next_back().unwrap())
             .collect::<Vec<_>>();
         let x = s[0].parse().unwrap();
         let m = s[1].parse().unwrap();
@@ -105,9 +105,9 @@ impl FromStr for Instruction {
 fn parse_input(path: &str) -> (HashMap<String, Instruction>, Vec<Part>) {
     let (mut instructions, mut parts) = (HashMap::new(), vec![]);
     let file = fs::read_to_string(path).expect("cannot read file");
-    let mut lines = file.lines().into_iter();
+    let mut lines = file.lines();
 
-    while let Some(line) = lines.next() {
+    for line in lines.by_ref() {
         if line.is_empty() {
             break;
         }
@@ -115,7 +115,7 @@ fn parse_input(path: &str) -> (HashMap<String, Instruction>, Vec<Part>) {
         instructions.insert(instruction.name.clone(), instruction);
     }
 
-    while let Some(line) = lines.next() {
+    for line in lines {
         let part = line.parse().unwrap();
         parts.push(part);
     }
@@ -0,0 +1,285 @@
+use aoc_2023::lcm;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    Broadcaster,
+    FlipFlop {
+        on: bool,
+    },
+    /// Remembers the last pulse of each input, in the order of `Module::inputs`
+    Conjunction {
+        memory: Vec<bool>,
+        high: usize,
+    },
+    /// Module that only appears as a destination (e.g., `rx`)
+    Sink,
+}
+
+#[derive(Debug, Clone)]
+struct Module {
+    name: String,
+    kind: Kind,
+    /// Destination modules, with the slot of this module among their inputs
+    outputs: Vec<(usize, usize)>,
+    inputs: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pulse {
+    from: usize,
+    to: usize,
+    /// Slot of `from` among the inputs of `to`
+    slot: usize,
+    high: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Network {
+    modules: Vec<Module>,
+    ids: HashMap<String, usize>,
+    broadcaster: usize,
+}
+
+fn parse_input(path: &str) -> Network {
+    let file = fs::read_to_string(path).expect("cannot read file");
+    let mut modules: Vec<Module> = Vec::new();
+    let mut ids = HashMap::new();
+    let mut id = |name: &str, modules: &mut Vec<Module>| {
+        *ids.entry(name.to_string()).or_insert_with(|| {
+            modules.push(Module {
+                name: name.to_string(),
+                kind: Kind::Sink,
+                outputs: Vec::new(),
+                inputs: Vec::new(),
+            });
+            modules.len() - 1
+        })
+    };
+
+    for line in file.lines().filter(|l| !l.is_empty()) {
+        let (source, destinations) = line.split_once(" -> ").expect("expected '->'");
+        let (kind, name) = match source.chars().next() {
+            Some('%') => (Kind::FlipFlop { on: false }, &source[1..]),
+            Some('&') => (
+                Kind::Conjunction {
+                    memory: Vec::new(),
+                    high: 0,
+                },
+                &source[1..],
+            ),
+            _ => (Kind::Broadcaster, source),
+        };
+        let from = id(name, &mut modules);
+        modules[from].kind = kind;
+        for destination in destinations.split(',').map(|d| d.trim()) {
+            let to = id(destination, &mut modules);
+            let slot = modules[to].inputs.len();
+            modules[to].inputs.push(from);
+            modules[from].outputs.push((to, slot));
+        }
+    }
+    for module in modules.iter_mut() {
+        if let Kind::Conjunction { memory, .. } = &mut module.kind {
+            *memory = vec![false; module.inputs.len()];
+        }
+    }
+
+    let broadcaster = modules
+        .iter()
+        .position(|m| m.kind == Kind::Broadcaster)
+        .expect("missing broadcaster");
+    Network {
+        modules,
+        ids,
+        broadcaster,
+    }
+}
+
+impl Network {
+    /// Turns every flip-flop off and makes every conjunction remember low pulses
+    fn reset(&mut self) {
+        for module in self.modules.iter_mut() {
+            match &mut module.kind {
+                Kind::FlipFlop { on } => *on = false,
+                Kind::Conjunction { memory, high } => {
+                    memory.fill(false);
+                    *high = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Pushes the button once, calling `observe` on every pulse in the order it is processed
+    fn press(&mut self, mut observe: impl FnMut(&Pulse)) {
+        let mut queue = VecDeque::new();
+        queue.push_back(Pulse {
+            from: self.broadcaster,
+            to: self.broadcaster,
+            slot: 0,
+            high: false,
+        });
+        while let Some(pulse) = queue.pop_front() {
+            observe(&pulse);
+            let module = &mut self.modules[pulse.to];
+            let high = match &mut module.kind {
+                Kind::Broadcaster => pulse.high,
+                Kind::FlipFlop { on } => {
+                    if pulse.high {
+                        continue;
+                    }
+                    *on = !*on;
+                    *on
+                }
+                Kind::Conjunction { memory, high } => {
+                    if memory[pulse.slot] != pulse.high {
+                        memory[pulse.slot] = pulse.high;
+                        match pulse.high {
+                            true => *high += 1,
+                            false => *high -= 1,
+                        }
+                    }
+                    *high < memory.len()
+                }
+                Kind::Sink => continue,
+            };
+            for &(to, slot) in module.outputs.iter() {
+                queue.push_back(Pulse {
+                    from: pulse.to,
+                    to,
+                    slot,
+                    high,
+                });
+            }
+        }
+    }
+}
+
+/// Chain of flip-flops started by the broadcaster that works as a binary counter:
+/// the conjunction fires when the flip-flops wired to it (the set bits) are all on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Counter {
+    flip_flops: Vec<usize>,
+    conjunction: usize,
+    period: usize,
+}
+
+/// Decodes the counter that starts with the given flip-flop, if it is wired like one
+fn decode_counter(network: &Network, first: usize) -> Option<Counter> {
+    let is_flip_flop = |id: usize| matches!(network.modules[id].kind, Kind::FlipFlop { .. });
+    let is_conjunction = |id: usize| matches!(network.modules[id].kind, Kind::Conjunction { .. });
+
+    let (mut flip_flops, mut conjunction, mut period) = (Vec::new(), None, 0);
+    let mut current = Some(first);
+    while let Some(id) = current {
+        let outputs = &network.modules[id].outputs;
+        if let Some(&(c, _)) = outputs.iter().find(|&&(to, _)| is_conjunction(to)) {
+            if conjunction.is_some_and(|other| other != c) {
+                return None;
+            }
+            conjunction = Some(c);
+            period |= 1 << flip_flops.len();
+        }
+        flip_flops.push(id);
+        current = outputs
+            .iter()
+            .map(|&(to, _)| to)
+            .find(|&to| is_flip_flop(to) && !flip_flops.contains(&to));
+    }
+    Some(Counter {
+        flip_flops,
+        conjunction: conjunction?,
+        period,
+    })
+}
+
+fn exercise1(network: &mut Network) -> usize {
+    network.reset();
+    let (mut low, mut high) = (0, 0);
+    for _ in 0..1000 {
+        network.press(|pulse| match pulse.high {
+            true => high += 1,
+            false => low += 1,
+        });
+    }
+    println!("{} low and {} high pulses", low, high);
+    low * high
+}
+
+/// Presses needed for `rx` to receive a low pulse. `rx` must be fed by a single
+/// conjunction, whose inputs are assumed to send a high pulse periodically, starting
+/// at their first period. `None` if the network is not wired that way, or if the answer
+/// does not fit in a `u128`.
+fn exercise2(network: &mut Network) -> Option<u128> {
+    let rx = *network.ids.get("rx")?;
+    let [feeder] = network.modules[rx].inputs[..] else {
+        return None;
+    };
+    if !matches!(network.modules[feeder].kind, Kind::Conjunction { .. }) {
+        return None;
+    }
+    let sources = network.modules[feeder].inputs.clone();
+
+    // press number of the first two high pulses of each source
+    let mut seen: Vec<Vec<usize>> = vec![Vec::new(); sources.len()];
+    network.reset();
+    let mut presses = 0;
+    while seen.iter().any(|s| s.len() < 2) {
+        presses += 1;
+        network.press(|pulse| {
+            if pulse.to == feeder && pulse.high {
+                let source = &mut seen[pulse.slot];
+                if source.last() != Some(&presses) {
+                    source.push(presses);
+                }
+            }
+        });
+        if presses > 1 << 20 {
+            return None;
+        }
+    }
+
+    let mut periods = Vec::new();
+    for (&source, presses) in sources.iter().zip(seen.iter()) {
+        let period = presses[1] - presses[0];
+        println!(
+            "  {} sends a high pulse every {} presses",
+            network.modules[source].name, period
+        );
+        if presses[0] != period {
+            return None;
+        }
+        periods.push(period);
+    }
+    periods
+        .into_iter()
+        .try_fold(1, |acc, period| lcm(acc, period as u128))
+}
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or("data/20_input.txt".to_string());
+    let mut network = parse_input(&path);
+
+    println!("exercise1: {}", exercise1(&mut network));
+    match exercise2(&mut network) {
+        Some(res) => println!("exercise2: {}", res),
+        None => println!("exercise2: no answer"),
+    }
+
+    let starts = network.modules[network.broadcaster].outputs.clone();
+    for (first, _) in starts {
+        if let Some(counter) = decode_counter(&network, first) {
+            println!(
+                "counter from {}: {} flip-flops into {}, period {}",
+                network.modules[first].name,
+                counter.flip_flops.len(),
+                network.modules[counter.conjunction].name,
+                counter.period
+            );
+        }
+    }
+}
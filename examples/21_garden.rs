@@ -0,0 +1,220 @@
+use aoc_2023::search::Dir;
+use aoc_2023::Point;
+use std::collections::HashSet;
+use std::fs;
+
+#[derive(Debug, Clone)]
+struct Garden {
+    rocks: Vec<Vec<bool>>,
+    start: Point,
+}
+
+fn parse_input(path: &str) -> Garden {
+    let file = fs::read_to_string(path).expect("cannot read file");
+    let mut start = None;
+    let mut rocks = Vec::new();
+    for (row, line) in file.lines().enumerate() {
+        if let Some(col) = line.find('S') {
+            start = Some(Point::new(row as isize, col as isize));
+        }
+        rocks.push(line.chars().map(|c| c == '#').collect());
+    }
+    Garden {
+        rocks,
+        start: start.expect("missing start"),
+    }
+}
+
+impl Garden {
+    fn n_rows(&self) -> usize {
+        self.rocks.len()
+    }
+
+    fn n_cols(&self) -> usize {
+        self.rocks[0].len()
+    }
+
+    /// Whether the point is a garden plot. In an infinite garden the map repeats in every
+    /// direction; otherwise points outside the map are not plots.
+    fn is_plot(&self, point: Point, infinite: bool) -> bool {
+        let (n_rows, n_cols) = (self.n_rows() as isize, self.n_cols() as isize);
+        let inside = (0..n_rows).contains(&point.row) && (0..n_cols).contains(&point.col);
+        if !infinite && !inside {
+            return false;
+        }
+        let (row, col) = (point.row.rem_euclid(n_rows), point.col.rem_euclid(n_cols));
+        !self.rocks[row as usize][col as usize]
+    }
+}
+
+/// Breadth-first search from the start that can be resumed to walk further. Only the last
+/// two layers are kept, since the neighbours of a plot are at most one step closer.
+struct Walk<'a> {
+    garden: &'a Garden,
+    infinite: bool,
+    previous: HashSet<Point>,
+    current: HashSet<Point>,
+    /// Number of plots at each distance from the start
+    counts: Vec<usize>,
+}
+
+impl<'a> Walk<'a> {
+    fn new(garden: &'a Garden, infinite: bool) -> Self {
+        Self {
+            garden,
+            infinite,
+            previous: HashSet::new(),
+            current: HashSet::from([garden.start]),
+            counts: vec![1],
+        }
+    }
+
+    /// Number of plots at each distance from the start, up to `max_steps`
+    fn counts(&mut self, max_steps: usize) -> &[usize] {
+        while self.counts.len() <= max_steps && !self.current.is_empty() {
+            let mut next = HashSet::new();
+            for &point in self.current.iter() {
+                for dir in Dir::ALL {
+                    let neighbour = point + dir.delta();
+                    if self.garden.is_plot(neighbour, self.infinite)
+                        && !self.previous.contains(&neighbour)
+                        && !self.current.contains(&neighbour)
+                    {
+                        next.insert(neighbour);
+                    }
+                }
+            }
+            self.counts.push(next.len());
+            self.previous = std::mem::replace(&mut self.current, next);
+        }
+        &self.counts[..self.counts.len().min(max_steps + 1)]
+    }
+}
+
+/// Plots where the elf can end after exactly `steps` steps. The elf can go back and forth
+/// between two plots, so a plot counts when it is reached in at most `steps` steps, and
+/// with the same parity.
+fn count_reachable(counts: &[usize], steps: usize) -> usize {
+    counts
+        .iter()
+        .enumerate()
+        .filter(|&(d, _)| d <= steps && (steps - d).is_multiple_of(2))
+        .map(|(_, &count)| count)
+        .sum()
+}
+
+fn brute_force(garden: &Garden, steps: usize, infinite: bool) -> usize {
+    count_reachable(Walk::new(garden, infinite).counts(steps), steps)
+}
+
+/// Periods fitted before giving up on finding a quadratic
+const MAX_PERIODS: usize = 16;
+
+/// Reachable plots for `steps = offset + period * k`, as a quadratic on `k`. The period is
+/// twice the side of the map, so every sample has the same parity: with an odd side,
+/// consecutive tiles are reached with opposite parities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Quadratic {
+    offset: usize,
+    period: usize,
+    /// First `k` where the values follow the quadratic
+    first: usize,
+    /// Values for `k = first, first + 1, first + 2`
+    samples: [usize; 3],
+}
+
+impl Quadratic {
+    /// Fits the quadratic for the step counts with the same remainder as `steps`, once the
+    /// frontier has left the first tiles: from the first `k` where three consecutive
+    /// second differences agree.
+    fn fit(garden: &Garden, steps: usize) -> Option<Self> {
+        if garden.n_rows() != garden.n_cols() {
+            return None;
+        }
+        let period = 2 * garden.n_rows();
+        let offset = steps % period;
+        let mut walk = Walk::new(garden, true);
+        let mut values = Vec::new();
+        for first in 0..MAX_PERIODS {
+            while values.len() < first + 5 {
+                let steps = offset + values.len() * period;
+                values.push(count_reachable(walk.counts(steps), steps) as i128);
+            }
+            let second = |k: usize| values[k + 2] - 2 * values[k + 1] + values[k];
+            if second(first) == second(first + 1) && second(first) == second(first + 2) {
+                let samples = [0, 1, 2].map(|i| values[first + i] as usize);
+                return Some(Self {
+                    offset,
+                    period,
+                    first,
+                    samples,
+                });
+            }
+        }
+        None
+    }
+
+    fn at(&self, steps: usize) -> Option<u128> {
+        if steps < self.offset || !(steps - self.offset).is_multiple_of(self.period) {
+            return None;
+        }
+        let k = (steps - self.offset) / self.period;
+        let k = k.checked_sub(self.first)? as i128;
+        let [y0, y1, y2] = self.samples.map(|s| s as i128);
+        let (d1, d2) = (y1 - y0, y2 - y1);
+        let res = y0 + k * d1 + k * (k - 1) / 2 * (d2 - d1);
+        u128::try_from(res).ok()
+    }
+}
+
+fn exercise1(garden: &Garden) -> usize {
+    brute_force(garden, 64, false)
+}
+
+fn exercise2(garden: &Garden, steps: usize) -> Option<u128> {
+    Quadratic::fit(garden, steps)?.at(steps)
+}
+
+/// Compares the extrapolation with a brute-force search for the next `checks` values of `k`
+fn verify(garden: &Garden, steps: usize, checks: usize) -> bool {
+    let Some(quadratic) = Quadratic::fit(garden, steps) else {
+        println!("verify: no quadratic found");
+        return false;
+    };
+    let last = quadratic.first + 4 + checks;
+    let mut walk = Walk::new(garden, true);
+    let counts = walk.counts(quadratic.offset + last * quadratic.period);
+    let mut ok = true;
+    for k in quadratic.first + 5..=last {
+        let steps = quadratic.offset + k * quadratic.period;
+        let expected = count_reachable(counts, steps) as u128;
+        let extrapolated = quadratic.at(steps);
+        println!(
+            "verify {} steps: brute force {}, extrapolated {:?}",
+            steps, expected, extrapolated
+        );
+        ok &= extrapolated == Some(expected);
+    }
+    ok
+}
+
+fn main() {
+    // `21_garden [path] [--verify]`
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let path = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map_or("data/21_input.txt", |p| p.as_str());
+    let garden = parse_input(path);
+
+    println!("exercise1: {}", exercise1(&garden));
+    match exercise2(&garden, 26501365) {
+        Some(res) => println!("exercise2: {}", res),
+        None => println!("exercise2: no quadratic found"),
+    }
+
+    if args.iter().any(|arg| arg == "--verify") {
+        let ok = verify(&garden, 26501365, 3);
+        println!("extrapolation {}", if ok { "verified" } else { "FAILED" });
+    }
+}
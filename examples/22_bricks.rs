@@ -0,0 +1,154 @@
+use std::fs;
+
+/// Brick between two corners, both included, as `[x, y, z]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Brick {
+    start: [usize; 3],
+    end: [usize; 3],
+}
+
+impl Brick {
+    /// `(x, y)` cells covered by the brick
+    fn footprint(&self) -> impl Iterator<Item = (usize, usize)> {
+        let (start, end) = (self.start, self.end);
+        (start[0]..=end[0]).flat_map(move |x| (start[1]..=end[1]).map(move |y| (x, y)))
+    }
+
+    fn height(&self) -> usize {
+        self.end[2] - self.start[2] + 1
+    }
+}
+
+fn parse_input(path: &str) -> Vec<Brick> {
+    let file = fs::read_to_string(path).expect("cannot read file");
+    file.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (a, b) = line.split_once('~').expect("expected '~'");
+            let corner = |s: &str| -> [usize; 3] {
+                let values = s.split(',').map(|v| v.parse().expect("invalid coordinate"));
+                values
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .expect("expected x,y,z")
+            };
+            let (a, b) = (corner(a), corner(b));
+            Brick {
+                start: [0, 1, 2].map(|i| a[i].min(b[i])),
+                end: [0, 1, 2].map(|i| a[i].max(b[i])),
+            }
+        })
+        .collect()
+}
+
+/// Bricks after falling, sorted from the ground up, with the bricks each one rests on
+#[derive(Debug, Clone)]
+struct Stack {
+    bricks: Vec<Brick>,
+    /// Bricks right below each brick; empty when it rests on the ground
+    supported_by: Vec<Vec<usize>>,
+    /// Bricks right above each brick
+    supports: Vec<Vec<usize>>,
+}
+
+/// Lets the bricks fall, lowest first, keeping the top of each `(x, y)` column
+fn settle(bricks: &[Brick]) -> Stack {
+    let mut bricks = bricks.to_vec();
+    bricks.sort_by_key(|b| b.start[2]);
+    let width = bricks.iter().map(|b| b.end[0] + 1).max().unwrap_or(0);
+    let depth = bricks.iter().map(|b| b.end[1] + 1).max().unwrap_or(0);
+    // height of each column and the brick on top of it
+    let mut heights: Vec<Vec<(usize, Option<usize>)>> = vec![vec![(0, None); depth]; width];
+
+    let mut supported_by = vec![Vec::new(); bricks.len()];
+    for (i, brick) in bricks.iter_mut().enumerate() {
+        let floor = brick
+            .footprint()
+            .map(|(x, y)| heights[x][y].0)
+            .max()
+            .unwrap_or(0);
+        for (x, y) in brick.footprint() {
+            if let (height, Some(below)) = heights[x][y] {
+                if height == floor && !supported_by[i].contains(&below) {
+                    supported_by[i].push(below);
+                }
+            }
+        }
+        let height = brick.height();
+        brick.start[2] = floor + 1;
+        brick.end[2] = floor + height;
+        for (x, y) in brick.footprint() {
+            heights[x][y] = (brick.end[2], Some(i));
+        }
+    }
+
+    let mut supports = vec![Vec::new(); bricks.len()];
+    for (i, below) in supported_by.iter().enumerate() {
+        for &b in below {
+            supports[b].push(i);
+        }
+    }
+    Stack {
+        bricks,
+        supported_by,
+        supports,
+    }
+}
+
+/// Immediate dominators of the support graph, rooted at the ground (index `n`): brick `d`
+/// dominates `b` when every path from the ground to `b` goes through `d`, so removing `d`
+/// makes `b` fall. Bricks are in topological order, so one pass is enough.
+fn dominators(stack: &Stack) -> Vec<usize> {
+    let ground = stack.bricks.len();
+    let mut idom = vec![ground; ground + 1];
+    let mut depth = vec![0; ground + 1];
+    for (i, below) in stack.supported_by.iter().enumerate() {
+        let mut dominator = match below.first() {
+            Some(&first) => first,
+            None => ground,
+        };
+        for &other in below.iter().skip(1) {
+            // common ancestor in the dominator tree
+            let mut other = other;
+            while dominator != other {
+                if depth[dominator] < depth[other] {
+                    other = idom[other];
+                } else {
+                    dominator = idom[dominator];
+                }
+            }
+        }
+        idom[i] = dominator;
+        depth[i] = depth[dominator] + 1;
+    }
+    idom
+}
+
+/// Bricks that can be removed without any other brick falling
+fn exercise1(stack: &Stack) -> usize {
+    let safe = |above: &Vec<usize>| above.iter().all(|&a| stack.supported_by[a].len() > 1);
+    stack.supports.iter().filter(|above| safe(above)).count()
+}
+
+/// Total number of other bricks that fall when each brick is removed: the size of its
+/// subtree in the dominator tree
+fn exercise2(stack: &Stack) -> usize {
+    let idom = dominators(stack);
+    let ground = stack.bricks.len();
+    let mut falling = vec![0; ground + 1];
+    // children appear after their dominators, so going backwards visits them first
+    for i in (0..ground).rev() {
+        falling[idom[i]] += falling[i] + 1;
+    }
+    falling[..ground].iter().sum()
+}
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or("data/22_input.txt".to_string());
+    let stack = settle(&parse_input(&path));
+
+    println!("exercise1: {}", exercise1(&stack));
+    println!("exercise2: {}", exercise2(&stack));
+}
@@ -0,0 +1,328 @@
+use aoc_2023::search::Dir;
+use aoc_2023::Point;
+use std::fs;
+
+fn parse_input(path: &str) -> Vec<Vec<char>> {
+    let file = fs::read_to_string(path).expect("cannot read file");
+    file.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().collect())
+        .collect()
+}
+
+fn tile(grid: &[Vec<char>], point: Point) -> char {
+    if point.row < 0 || point.col < 0 {
+        return '#';
+    }
+    let row = grid.get(point.row as usize);
+    *row.and_then(|r| r.get(point.col as usize)).unwrap_or(&'#')
+}
+
+/// Whether a tile can be entered moving towards `dir`, when slopes are honoured
+fn can_enter(grid: &[Vec<char>], point: Point, dir: Dir) -> bool {
+    match tile(grid, point) {
+        '#' => false,
+        '^' => dir == Dir::North,
+        '>' => dir == Dir::East,
+        'v' => dir == Dir::South,
+        '<' => dir == Dir::West,
+        _ => true,
+    }
+}
+
+/// Trail between two junctions, without crossing any other
+#[derive(Debug, Clone)]
+struct Edge {
+    to: usize,
+    length: usize,
+    /// Whether the trail can be followed in this direction when slopes are honoured
+    downhill: bool,
+    /// Tiles of the trail, excluding the first junction
+    tiles: Vec<Point>,
+}
+
+/// The trails compressed into a graph whose nodes are the start, the end and the tiles
+/// where trails fork
+#[derive(Debug, Clone)]
+struct Trails {
+    junctions: Vec<Point>,
+    /// Outgoing edges of each junction, in both directions of every trail
+    edges: Vec<Vec<Edge>>,
+    start: usize,
+    end: usize,
+}
+
+impl Trails {
+    fn new(grid: &[Vec<char>]) -> Self {
+        let open = |point: Point| tile(grid, point) != '#';
+        let last = grid.len() as isize - 1;
+        let find = |row: isize| {
+            let col = grid[row as usize].iter().position(|&c| c == '.');
+            Point::new(row, col.expect("missing start or end") as isize)
+        };
+        let (start, end) = (find(0), find(last));
+
+        let mut junctions = vec![start, end];
+        for (row, line) in grid.iter().enumerate() {
+            for col in 0..line.len() {
+                let point = Point::new(row as isize, col as isize);
+                let exits = Dir::ALL.iter().filter(|d| open(point + d.delta()));
+                if open(point) && exits.count() > 2 {
+                    junctions.push(point);
+                }
+            }
+        }
+
+        let mut edges = vec![Vec::new(); junctions.len()];
+        for (from, &junction) in junctions.iter().enumerate() {
+            for dir in Dir::ALL {
+                if let Some(edge) = Self::walk(grid, &junctions, junction, dir) {
+                    edges[from].push(edge);
+                }
+            }
+        }
+        Self {
+            junctions,
+            edges,
+            start: 0,
+            end: 1,
+        }
+    }
+
+    /// Follows the trail leaving `junction` towards `dir` up to the next junction
+    fn walk(grid: &[Vec<char>], junctions: &[Point], junction: Point, dir: Dir) -> Option<Edge> {
+        let (mut point, mut dir) = (junction + dir.delta(), dir);
+        let mut downhill = true;
+        let mut tiles = Vec::new();
+        loop {
+            if tile(grid, point) == '#' {
+                return None;
+            }
+            downhill &= can_enter(grid, point, dir);
+            tiles.push(point);
+            if let Some(to) = junctions.iter().position(|&j| j == point) {
+                return Some(Edge {
+                    to,
+                    length: tiles.len(),
+                    downhill,
+                    tiles,
+                });
+            }
+            // corridors have a single way forward; dead ends have none
+            dir = [dir, dir.left(), dir.right()]
+                .into_iter()
+                .find(|d| tile(grid, point + d.delta()) != '#')?;
+            point = point + dir.delta();
+        }
+    }
+}
+
+/// Hike through the junctions, as `(junction, index of the edge taken)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Route {
+    length: usize,
+    steps: Vec<(usize, usize)>,
+}
+
+impl Route {
+    fn tiles<'a>(&'a self, trails: &'a Trails) -> impl Iterator<Item = Point> + 'a {
+        let first = std::iter::once(trails.junctions[trails.start]);
+        let rest = self.steps.iter();
+        first.chain(rest.flat_map(|&(from, edge)| trails.edges[from][edge].tiles.iter().copied()))
+    }
+
+    /// The map with the tiles of the route marked as `O`
+    fn render(&self, trails: &Trails, grid: &[Vec<char>]) -> String {
+        let mut grid = grid.to_vec();
+        for point in self.tiles(trails) {
+            grid[point.row as usize][point.col as usize] = 'O';
+        }
+        grid.iter()
+            .map(|line| line.iter().collect::<String>() + "\n")
+            .collect()
+    }
+
+    fn describe(&self, trails: &Trails) -> String {
+        let junctions = std::iter::once(trails.start)
+            .chain(
+                self.steps
+                    .iter()
+                    .map(|&(from, edge)| trails.edges[from][edge].to),
+            )
+            .map(|j| trails.junctions[j].to_string());
+        junctions.collect::<Vec<_>>().join(" -> ")
+    }
+}
+
+/// Longest hike honouring slopes. The downhill trails form a DAG, so junctions are
+/// processed in topological order. Trails into the start or out of the end are ignored,
+/// as a hike never goes through them. `None` if the trails have a cycle or the end cannot
+/// be reached.
+fn exercise1(trails: &Trails) -> Option<Route> {
+    let n = trails.junctions.len();
+    let downhill = |from: usize| {
+        let edges = trails.edges[from].iter().enumerate();
+        let open = from != trails.end;
+        edges.filter(move |(_, e)| open && e.downhill && e.to != trails.start)
+    };
+    let mut reachable = vec![false; n];
+    reachable[trails.start] = true;
+    let mut stack = vec![trails.start];
+    while let Some(from) = stack.pop() {
+        for (_, edge) in downhill(from) {
+            if !reachable[edge.to] {
+                reachable[edge.to] = true;
+                stack.push(edge.to);
+            }
+        }
+    }
+    let mut incoming = vec![0; n];
+    for from in (0..n).filter(|&j| reachable[j]) {
+        for (_, edge) in downhill(from) {
+            incoming[edge.to] += 1;
+        }
+    }
+
+    // longest distance to each junction and the step used to reach it
+    let mut best = vec![None; n];
+    let mut prev = vec![None; n];
+    best[trails.start] = Some(0);
+    let mut ready = vec![trails.start];
+    let mut processed = 0;
+    while let Some(from) = ready.pop() {
+        processed += 1;
+        for (i, edge) in downhill(from) {
+            if let Some(length) = best[from].map(|length| length + edge.length) {
+                if best[edge.to].is_none_or(|other| other < length) {
+                    best[edge.to] = Some(length);
+                    prev[edge.to] = Some((from, i));
+                }
+            }
+            incoming[edge.to] -= 1;
+            if incoming[edge.to] == 0 {
+                ready.push(edge.to);
+            }
+        }
+    }
+    if processed < reachable.iter().filter(|&&r| r).count() {
+        return None;
+    }
+
+    let length = best[trails.end]?;
+    let mut steps = Vec::new();
+    let mut current = trails.end;
+    while let Some((from, edge)) = prev[current] {
+        steps.push((from, edge));
+        current = from;
+    }
+    steps.reverse();
+    Some(Route { length, steps })
+}
+
+/// State of the exhaustive search of exercise 2
+struct Hike<'a> {
+    trails: &'a Trails,
+    /// Junctions that are next to the end: once there, the hike must go to the end,
+    /// or it could never reach it
+    last: Option<usize>,
+    /// Longest trail of each junction, to bound what the unvisited ones can add
+    longest: Vec<usize>,
+    steps: Vec<(usize, usize)>,
+    best: Option<Route>,
+}
+
+impl Hike<'_> {
+    fn search(&mut self, junction: usize, visited: u128, length: usize, remaining: usize) {
+        if junction == self.trails.end {
+            if self.best.as_ref().is_none_or(|b| b.length < length) {
+                self.best = Some(Route {
+                    length,
+                    steps: self.steps.clone(),
+                });
+            }
+            return;
+        }
+        if self
+            .best
+            .as_ref()
+            .is_some_and(|b| length + remaining <= b.length)
+        {
+            return;
+        }
+        for (i, edge) in self.trails.edges[junction].iter().enumerate() {
+            if visited & 1 << edge.to != 0 {
+                continue;
+            }
+            if Some(junction) == self.last && edge.to != self.trails.end {
+                continue;
+            }
+            self.steps.push((junction, i));
+            let remaining = remaining - self.longest[edge.to];
+            self.search(
+                edge.to,
+                visited | 1 << edge.to,
+                length + edge.length,
+                remaining,
+            );
+            self.steps.pop();
+        }
+    }
+}
+
+/// Longest hike ignoring slopes: an exhaustive search over simple paths, with the
+/// visited junctions as a bitmask. `None` if there are more than 128 junctions.
+fn exercise2(trails: &Trails) -> Option<Route> {
+    let n = trails.junctions.len();
+    if n > 128 {
+        return None;
+    }
+    let longest = (0..n)
+        .map(|j| trails.edges[j].iter().map(|e| e.length).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    let to_end = (0..n).filter(|&j| trails.edges[j].iter().any(|e| e.to == trails.end));
+    let to_end = to_end.collect::<Vec<_>>();
+    let mut hike = Hike {
+        trails,
+        last: match to_end[..] {
+            [last] => Some(last),
+            _ => None,
+        },
+        longest: longest.clone(),
+        steps: Vec::new(),
+        best: None,
+    };
+    let remaining = longest.iter().sum::<usize>() - longest[trails.start];
+    hike.search(trails.start, 1 << trails.start, 0, remaining);
+    hike.best
+}
+
+fn main() {
+    // `23_hike [path] [--show]`
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let path = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map_or("data/23_input.txt", |p| p.as_str());
+    let show = args.iter().any(|arg| arg == "--show");
+    let grid = parse_input(path);
+    let trails = Trails::new(&grid);
+    println!(
+        "{} junctions, {} trails",
+        trails.junctions.len(),
+        trails.edges.iter().map(|e| e.len()).sum::<usize>() / 2
+    );
+
+    // part 2 can take a while, so each part is printed as soon as it is solved
+    let exercises: [fn(&Trails) -> Option<Route>; 2] = [exercise1, exercise2];
+    for (i, exercise) in exercises.iter().enumerate() {
+        let Some(route) = exercise(&trails) else {
+            println!("exercise{}: no route", i + 1);
+            continue;
+        };
+        println!("exercise{}: {}", i + 1, route.length);
+        println!("  {}", route.describe(&trails));
+        if show {
+            print!("{}", route.render(&trails, &grid));
+        }
+    }
+}
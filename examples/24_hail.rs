@@ -0,0 +1,292 @@
+use aoc_2023::gcd;
+use std::cmp::Ordering;
+use std::fs;
+
+/// Exact fraction, always reduced and with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    fn new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        let g = gcd(num.unsigned_abs(), den.unsigned_abs()) as i128;
+        let sign = den.signum();
+        Some(Self {
+            num: sign * num / g,
+            den: sign * den / g,
+        })
+    }
+
+    fn int(n: i128) -> Self {
+        Self { num: n, den: 1 }
+    }
+
+    fn add(self, other: Self) -> Option<Self> {
+        let num = self
+            .num
+            .checked_mul(other.den)?
+            .checked_add(other.num.checked_mul(self.den)?)?;
+        Self::new(num, self.den.checked_mul(other.den)?)
+    }
+
+    fn sub(self, other: Self) -> Option<Self> {
+        self.add(Self::int(-1).mul(other)?)
+    }
+
+    fn mul(self, other: Self) -> Option<Self> {
+        Self::new(
+            self.num.checked_mul(other.num)?,
+            self.den.checked_mul(other.den)?,
+        )
+    }
+
+    fn div(self, other: Self) -> Option<Self> {
+        Self::new(
+            self.num.checked_mul(other.den)?,
+            self.den.checked_mul(other.num)?,
+        )
+    }
+
+    fn to_integer(self) -> Option<i128> {
+        (self.den == 1).then_some(self.num)
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ratio {
+    /// Compares the integer parts, then the reciprocals of the remainders, as in a
+    /// continued fraction. Unlike cross products, this cannot overflow.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (
+            self.num.div_euclid(self.den),
+            other.num.div_euclid(other.den),
+        );
+        if a != b {
+            return a.cmp(&b);
+        }
+        let (r, s) = (
+            self.num.rem_euclid(self.den),
+            other.num.rem_euclid(other.den),
+        );
+        match (r, s) {
+            (0, 0) => Ordering::Equal,
+            (0, _) => Ordering::Less,
+            (_, 0) => Ordering::Greater,
+            // r / den < s / other.den exactly when den / r > other.den / s
+            _ => Self {
+                num: other.den,
+                den: s,
+            }
+            .cmp(&Self {
+                num: self.den,
+                den: r,
+            }),
+        }
+    }
+}
+
+type Vec3 = [i128; 3];
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: Vec3, b: Vec3) -> Option<i128> {
+    let terms = [0, 1, 2].map(|i| a[i].checked_mul(b[i]));
+    terms
+        .into_iter()
+        .try_fold(0i128, |acc, term| acc.checked_add(term?))
+}
+
+fn cross(a: Vec3, b: Vec3) -> Option<Vec3> {
+    let component =
+        |i: usize, j: usize| a[i].checked_mul(b[j])?.checked_sub(a[j].checked_mul(b[i])?);
+    Some([component(1, 2)?, component(2, 0)?, component(0, 1)?])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hailstone {
+    position: Vec3,
+    velocity: Vec3,
+}
+
+impl Hailstone {
+    /// Position at time `t`
+    fn at(&self, t: Ratio) -> Option<[Ratio; 3]> {
+        let coordinate =
+            |i: usize| Ratio::int(self.position[i]).add(t.mul(Ratio::int(self.velocity[i]))?);
+        Some([coordinate(0)?, coordinate(1)?, coordinate(2)?])
+    }
+}
+
+fn parse_input(path: &str) -> Vec<Hailstone> {
+    let file = fs::read_to_string(path).expect("cannot read file");
+    let vector = |s: &str| -> Vec3 {
+        let values = s
+            .split(',')
+            .map(|v| v.trim().parse().expect("invalid number"));
+        let values = values.collect::<Vec<_>>();
+        values.try_into().expect("expected three coordinates")
+    };
+    file.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (position, velocity) = line.split_once('@').expect("expected '@'");
+            Hailstone {
+                position: vector(position),
+                velocity: vector(velocity),
+            }
+        })
+        .collect()
+}
+
+/// Point where the paths of two hailstones cross in the XY plane, if both reach it in
+/// the future. Parallel paths never cross.
+fn crossing_xy(a: &Hailstone, b: &Hailstone) -> Option<[Ratio; 2]> {
+    // a.p + t * a.v = b.p + s * b.v, solved with Cramer's rule
+    let (dx, dy) = (b.position[0] - a.position[0], b.position[1] - a.position[1]);
+    let det = b.velocity[0] * a.velocity[1] - a.velocity[0] * b.velocity[1];
+    let t = Ratio::new(b.velocity[0] * dy - b.velocity[1] * dx, det)?;
+    let s = Ratio::new(a.velocity[0] * dy - a.velocity[1] * dx, det)?;
+    if t.num < 0 || s.num < 0 {
+        return None;
+    }
+    let point = a.at(t)?;
+    Some([point[0], point[1]])
+}
+
+fn exercise1(hailstones: &[Hailstone], area: (i128, i128)) -> usize {
+    let (low, high) = (Ratio::int(area.0), Ratio::int(area.1));
+    let inside = |p: &Ratio| low <= *p && *p <= high;
+    let mut res = 0;
+    for (i, a) in hailstones.iter().enumerate() {
+        for b in hailstones[i + 1..].iter() {
+            if crossing_xy(a, b).is_some_and(|point| point.iter().all(inside)) {
+                res += 1;
+            }
+        }
+    }
+    res
+}
+
+/// Time when a hailstone crosses the plane through the origin with the given normal
+fn plane_crossing(hailstone: &Hailstone, normal: Vec3) -> Option<Ratio> {
+    let speed = dot(hailstone.velocity, normal)?;
+    Ratio::new(-dot(hailstone.position, normal)?, speed)
+}
+
+/// Position and velocity of a rock thrown so that it hits every hailstone.
+///
+/// In the frame of the first hailstone, which then stays at the origin, the rock goes
+/// through the origin, so its path lies in the plane through the origin that contains
+/// the path of another hailstone. Any other two hailstones cross that plane where the
+/// rock hits them, which gives its velocity and position. All this fits in an `i128`
+/// for the puzzle sizes, where solving the full linear system would not. Needs at least
+/// four hailstones: the reference, one for the plane and two crossing it.
+fn throw(hailstones: &[Hailstone]) -> Option<Hailstone> {
+    let reference = hailstones.first()?;
+    let relative = hailstones
+        .iter()
+        .map(|h| Hailstone {
+            position: sub(h.position, reference.position),
+            velocity: sub(h.velocity, reference.velocity),
+        })
+        .collect::<Vec<_>>();
+
+    // a plane can be degenerate or overflow, in which case the next one may still work
+    (1..relative.len())
+        .filter_map(|i| throw_in_plane(&relative, i))
+        .map(|rock| Hailstone {
+            position: [0, 1, 2].map(|k| rock.position[k] + reference.position[k]),
+            velocity: [0, 1, 2].map(|k| rock.velocity[k] + reference.velocity[k]),
+        })
+        .find(|rock| hailstones.iter().all(|h| hits_at(rock, h).is_some()))
+}
+
+/// Rock found from the plane of `relative[plane]`, in the frame of `relative[0]`
+fn throw_in_plane(relative: &[Hailstone], plane: usize) -> Option<Hailstone> {
+    let normal = cross(relative[plane].position, relative[plane].velocity)?;
+    if normal == [0; 3] {
+        return None;
+    }
+    // two hailstones that cross the plane at different times
+    let mut hits = relative
+        .iter()
+        .enumerate()
+        .filter(|&(j, _)| j != 0 && j != plane)
+        .filter_map(|(_, h)| Some((plane_crossing(h, normal)?, h)));
+    let (t1, h1) = hits.next()?;
+    let (t2, h2) = hits.find(|(t, _)| *t != t1)?;
+
+    let (p1, p2) = (h1.at(t1)?, h2.at(t2)?);
+    let dt = t2.sub(t1)?;
+    let mut position = [0; 3];
+    let mut velocity = [0; 3];
+    for k in 0..3 {
+        let v = p2[k].sub(p1[k])?.div(dt)?;
+        let p = p1[k].sub(v.mul(t1)?)?;
+        velocity[k] = v.to_integer()?;
+        position[k] = p.to_integer()?;
+    }
+    Some(Hailstone { position, velocity })
+}
+
+/// Time when the rock hits the hailstone, if it does
+fn hits_at(rock: &Hailstone, hailstone: &Hailstone) -> Option<i128> {
+    let (dp, dv) = (
+        sub(hailstone.position, rock.position),
+        sub(rock.velocity, hailstone.velocity),
+    );
+    // same position at time t: dp == t * dv on every axis
+    let mut time = None;
+    for k in 0..3 {
+        match (dp[k], dv[k]) {
+            (0, 0) => {}
+            (_, 0) => return None,
+            (p, v) if p % v != 0 => return None,
+            (p, v) if time.is_some_and(|t| t != p / v) => return None,
+            (p, v) => time = Some(p / v),
+        }
+    }
+    let time = time.unwrap_or(0);
+    (time >= 0).then_some(time)
+}
+
+fn exercise2(hailstones: &[Hailstone]) -> Option<i128> {
+    let rock = throw(hailstones)?;
+    println!(
+        "rock thrown from {:?} with velocity {:?}",
+        rock.position, rock.velocity
+    );
+    Some(rock.position.iter().sum())
+}
+
+fn main() {
+    // `24_hail [path [low high]]`
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let path = args.first().map_or("data/24_input.txt", |p| p.as_str());
+    let area = match &args[..] {
+        [_, low, high, ..] => (
+            low.parse().expect("invalid area"),
+            high.parse().expect("invalid area"),
+        ),
+        _ => (200_000_000_000_000, 400_000_000_000_000),
+    };
+    let hailstones = parse_input(path);
+
+    println!("exercise1: {}", exercise1(&hailstones, area));
+    match exercise2(&hailstones) {
+        Some(res) => println!("exercise2: {}", res),
+        None => println!("exercise2: no rock hits every hailstone"),
+    }
+}
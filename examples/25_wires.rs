@@ -0,0 +1,166 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+
+/// Undirected graph of components, with every wire as an edge
+#[derive(Debug, Clone)]
+struct Graph {
+    names: Vec<String>,
+    edges: Vec<(usize, usize)>,
+    /// Neighbours of each node, with the index of the edge that connects them
+    adjacency: Vec<Vec<(usize, usize)>>,
+}
+
+fn parse_input(path: &str) -> Graph {
+    let file = fs::read_to_string(path).expect("cannot read file");
+    let mut ids = HashMap::new();
+    let mut names = Vec::new();
+    let mut id = |name: &str| {
+        *ids.entry(name.to_string()).or_insert_with(|| {
+            names.push(name.to_string());
+            names.len() - 1
+        })
+    };
+    let mut edges = Vec::new();
+    for line in file.lines().filter(|line| !line.is_empty()) {
+        let (from, to) = line.split_once(':').expect("expected ':'");
+        let from = id(from.trim());
+        for to in to.split_whitespace() {
+            edges.push((from, id(to)));
+        }
+    }
+
+    let mut adjacency = vec![Vec::new(); names.len()];
+    for (i, &(a, b)) in edges.iter().enumerate() {
+        adjacency[a].push((b, i));
+        adjacency[b].push((a, i));
+    }
+    Graph {
+        names,
+        edges,
+        adjacency,
+    }
+}
+
+/// Partition of the nodes with the fewest edges between both sides
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cut {
+    /// Whether each node is on the side of the source
+    side: Vec<bool>,
+    edges: Vec<usize>,
+}
+
+impl Graph {
+    /// Nodes sorted by their distance to `source`, farthest (or unreachable) first
+    fn by_distance(&self, source: usize) -> Vec<usize> {
+        let mut dist = vec![usize::MAX; self.names.len()];
+        dist[source] = 0;
+        let mut order = vec![source];
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            for &(next, _) in self.adjacency[node].iter() {
+                if dist[next] == usize::MAX {
+                    dist[next] = dist[node] + 1;
+                    order.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        order.extend((0..self.names.len()).filter(|&node| dist[node] == usize::MAX));
+        order.reverse();
+        order
+    }
+
+    /// Minimum cut between `source` and `sink`, with every wire carrying one unit of flow
+    /// (Edmonds–Karp). Stops once the flow exceeds `limit`, as the cut is not wanted then.
+    fn min_cut(&self, source: usize, sink: usize, limit: usize) -> Option<Cut> {
+        // flow along each edge, positive from its first node to its second one
+        let mut flow = vec![0i8; self.edges.len()];
+        let residual = |flow: &[i8], from: usize, edge: usize| match self.edges[edge].0 == from {
+            true => 1 - flow[edge],
+            false => 1 + flow[edge],
+        };
+        let mut value = 0;
+        loop {
+            // shortest augmenting path, as the edge used to reach each node
+            let mut prev = vec![None; self.names.len()];
+            let mut visited = vec![false; self.names.len()];
+            visited[source] = true;
+            let mut queue = VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                for &(next, edge) in self.adjacency[node].iter() {
+                    if !visited[next] && residual(&flow, node, edge) > 0 {
+                        visited[next] = true;
+                        prev[next] = Some((node, edge));
+                        queue.push_back(next);
+                    }
+                }
+            }
+            if !visited[sink] {
+                // the nodes still reachable from the source form its side of the cut
+                let edges = (0..self.edges.len())
+                    .filter(|&e| visited[self.edges[e].0] != visited[self.edges[e].1])
+                    .collect();
+                return Some(Cut {
+                    side: visited,
+                    edges,
+                });
+            }
+            value += 1;
+            if value > limit {
+                return None;
+            }
+            let mut node = sink;
+            while let Some((from, edge)) = prev[node] {
+                flow[edge] += if self.edges[edge].0 == from { 1 } else { -1 };
+                node = from;
+            }
+        }
+    }
+
+    /// Global minimum cut: the smallest of the cuts between a fixed source and every
+    /// other node, as some node must be on the other side. Farther nodes are tried first,
+    /// as they are more likely to be there, so later searches stop early.
+    fn global_min_cut(&self) -> Option<Cut> {
+        let source = 0;
+        let mut best: Option<Cut> = None;
+        for sink in self.by_distance(source) {
+            if sink == source {
+                continue;
+            }
+            let limit = match &best {
+                // nothing beats a disconnected graph
+                Some(b) if b.edges.is_empty() => break,
+                Some(b) => b.edges.len() - 1,
+                None => usize::MAX,
+            };
+            if let Some(cut) = self.min_cut(source, sink, limit) {
+                best = Some(cut);
+            }
+        }
+        best
+    }
+}
+
+fn exercise1(graph: &Graph) -> Option<usize> {
+    let cut = graph.global_min_cut()?;
+    let sizes = [true, false].map(|side| cut.side.iter().filter(|&&s| s == side).count());
+    println!("{} wires to cut:", cut.edges.len());
+    for &edge in cut.edges.iter() {
+        let (a, b) = graph.edges[edge];
+        println!("  {}/{}", graph.names[a], graph.names[b]);
+    }
+    println!("components of {} and {}", sizes[0], sizes[1]);
+    Some(sizes[0] * sizes[1])
+}
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or("data/25_input.txt".to_string());
+    let graph = parse_input(&path);
+
+    match exercise1(&graph) {
+        Some(res) => println!("exercise1: {}", res),
+        None => println!("exercise1: no cut, fewer than two components"),
+    }
+}
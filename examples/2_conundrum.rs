@@ -7,7 +7,7 @@ fn parse_input(path: &str) -> Vec<Vec<(u32, u32, u32)>> {
     let reader = BufReader::new(file);
     for line in reader.lines().map_while(Result::ok) {
         let mut game = Vec::new();
-        let line = line.split(':').last().unwrap();
+        let line = line.split(':').next_back().unwrap();
         for reveal in line.split(';') {
             let (mut red, mut green, mut blue) = (0, 0, 0);
             for color in reveal.split(',').map(|s| s.trim()) {
@@ -10,7 +10,7 @@ fn parse_input(path: &str) -> Vec<(HashSet<u32>, HashSet<u32>)> {
     let file = File::open(path).expect("input file not found");
     let reader = BufReader::new(file);
     for line in reader.lines().map_while(Result::ok) {
-        let line = line.split(':').last().unwrap();
+        let line = line.split(':').next_back().unwrap();
         let numbers = line.split('|').map(|s| s.trim()).collect::<Vec<_>>();
 
         let winning: HashSet<u32> = numbers[0]
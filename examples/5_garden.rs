@@ -1,3 +1,5 @@
+use aoc_2023::check::{shrink_usize, shrink_vec, Checker};
+use std::collections::BTreeSet;
 use std::{ops::Range, str::FromStr};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -22,7 +24,7 @@ impl FromStr for Scenario {
             .next()
             .ok_or("missing seeds")?
             .split(':')
-            .last()
+            .next_back()
             .unwrap()
             .split_whitespace()
             .map(|s| s.parse::<u32>().unwrap() as usize)
@@ -49,7 +51,7 @@ impl FromStr for Scenario {
                     source,
                 });
             }
-            transformations.sort_by(|a, b| a.destination.start.cmp(&b.destination.start));
+            transformations.sort_by_key(|a| a.destination.start);
             stages.push(transformations);
         }
 
@@ -92,13 +94,13 @@ fn exercise_2(scenario: &Scenario) -> Option<usize> {
     seeds = compress_ranges(seeds);
     println!("Seeds: {:?}", seeds);
 
-    let res = prueba2(&scenario.stages, 0, &seeds);
+    let res = prueba2(&scenario.stages, 0, &seeds, true);
 
     res.first().map(|r| r.start)
 }
 
 fn compress_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
-    ranges.sort_by(|a, b| a.start.cmp(&b.start));
+    ranges.sort_by_key(|a| a.start);
     let mut res = Vec::new();
 
     let mut i = 0;
@@ -119,16 +121,20 @@ fn compress_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
     res
 }
 
+/// Maps the ranges through the stages from `level` on, printing each step if `verbose`
 fn prueba2(
     stages: &[Vec<Transformation>],
     level: usize,
     origin_ranges: &[Range<usize>],
+    verbose: bool,
 ) -> Vec<Range<usize>> {
     if level >= stages.len() {
         return origin_ranges.to_vec();
     }
 
-    println!("LEVEL {}", level);
+    if verbose {
+        println!("LEVEL {}", level);
+    }
     // println!("    Origin ranges: {:?}", origin_ranges);
 
     // get corresponding stage
@@ -163,10 +169,12 @@ fn prueba2(
         }
     }
     let mut destination_ranges = compress_ranges(uncovered);
-    println!(
-        "    Uncovered ranges (propagate as is): {:?}",
-        destination_ranges
-    );
+    if verbose {
+        println!(
+            "    Uncovered ranges (propagate as is): {:?}",
+            destination_ranges
+        );
+    }
 
     // compute potential origin ranges
     for range in origin_ranges {
@@ -177,23 +185,130 @@ fn prueba2(
             // check if range is valid
             if start < end {
                 let destination = &trans.destination;
-                print!("    Covered range: {}..{} -> ", start, end);
-                let start = destination.start + start - origin.start;
-                let end = destination.start + end - origin.start;
-                println!("{}..{}", start, end);
-                destination_ranges.push(start..end);
+                let new_start = destination.start + start - origin.start;
+                let new_end = destination.start + end - origin.start;
+                if verbose {
+                    println!(
+                        "    Covered range: {}..{} -> {}..{}",
+                        start, end, new_start, new_end
+                    );
+                }
+                destination_ranges.push(new_start..new_end);
             }
         }
     }
-    destination_ranges.sort_by(|a, b| a.start.cmp(&b.start));
+    destination_ranges.sort_by_key(|a| a.start);
     destination_ranges = compress_ranges(destination_ranges);
-    println!("    Destination ranges: {:?}", destination_ranges);
+    if verbose {
+        println!("    Destination ranges: {:?}", destination_ranges);
+    }
 
-    prueba2(stages, level + 1, &destination_ranges)
+    prueba2(stages, level + 1, &destination_ranges, verbose)
+}
+
+/// Locations reached from the seed ranges, mapping every seed on its own
+fn brute_force(stages: &[Vec<Transformation>], seeds: &[Range<usize>]) -> BTreeSet<usize> {
+    let location = |seed: usize| {
+        stages.iter().fold(seed, |value, stage| {
+            let found = stage.iter().find(|t| t.source.contains(&value));
+            found.map_or(value, |t| t.destination.start + value - t.source.start)
+        })
+    };
+    seeds.iter().flat_map(|r| r.clone().map(location)).collect()
+}
+
+/// Smaller versions of a stage, keeping its sources disjoint and its sorting
+fn shrink_stage(stage: &[Transformation]) -> Vec<Vec<Transformation>> {
+    shrink_vec(stage, |t| {
+        let len = t.source.len();
+        let shorter = shrink_usize(len)
+            .into_iter()
+            .filter(|&l| l > 0)
+            .map(|l| Transformation {
+                destination: t.destination.start..t.destination.start + l,
+                source: t.source.start..t.source.start + l,
+            });
+        let moved = shrink_usize(t.destination.start)
+            .into_iter()
+            .map(|d| Transformation {
+                destination: d..d + len,
+                source: t.source.clone(),
+            });
+        shorter.chain(moved).collect()
+    })
+    .into_iter()
+    .map(|mut stage| {
+        stage.sort_by_key(|a| a.destination.start);
+        stage
+    })
+    .collect()
+}
+
+/// Compares the range splitting of exercise 2 with mapping every seed of small almanacs
+fn check(checker: &Checker) -> bool {
+    let range = |start: usize, len: usize| start..start + len;
+    checker.check(
+        "exercise 2",
+        |rng| {
+            let seeds = (0..rng.range(1..4))
+                .map(|_| range(rng.range(0..40), rng.range(1..12)))
+                .collect::<Vec<_>>();
+            let stages = (0..rng.range(1..4))
+                .map(|_| {
+                    // disjoint sources, between random cut points
+                    let mut cuts = (0..rng.range(0..8))
+                        .map(|_| rng.range(0..50))
+                        .collect::<Vec<_>>();
+                    cuts.sort();
+                    cuts.dedup();
+                    let mut stage = Vec::new();
+                    for w in cuts.windows(2) {
+                        if rng.chance(0.7) {
+                            stage.push(Transformation {
+                                destination: range(rng.range(0..50), w[1] - w[0]),
+                                source: w[0]..w[1],
+                            });
+                        }
+                    }
+                    stage.sort_by_key(|a| a.destination.start);
+                    stage
+                })
+                .collect::<Vec<_>>();
+            (seeds, stages)
+        },
+        |(seeds, stages)| {
+            let fewer_seeds = shrink_vec(seeds, |r| {
+                let shorter = shrink_usize(r.len()).into_iter().filter(|&l| l > 0);
+                shorter.map(|l| range(r.start, l)).collect()
+            });
+            let fewer_seeds = fewer_seeds.into_iter().filter(|s| !s.is_empty());
+            let simpler_stages = shrink_vec(stages, |stage| shrink_stage(stage));
+            let res = fewer_seeds.map(|s| (s, stages.clone()));
+            res.chain(simpler_stages.into_iter().map(|s| (seeds.clone(), s)))
+                .collect()
+        },
+        |(seeds, stages)| {
+            let ranges = prueba2(stages, 0, &compress_ranges(seeds.clone()), false);
+            let fast = ranges.into_iter().flatten().collect::<BTreeSet<_>>();
+            let slow = brute_force(stages, seeds);
+            match fast == slow {
+                true => Ok(()),
+                false => Err(format!(
+                    "range splitting gives {:?}, brute force {:?}",
+                    fast, slow
+                )),
+            }
+        },
+    )
 }
 
 fn main() {
+    // `--check [cases] [seed]` compares exercise 2 with a brute force instead
+    Checker::run_from_args(check);
+
     let scenario = parse_input("data/5_input.txt");
     println!("Exercise 1: {}", exercise_1(&scenario));
     println!("Exercise 2: {:?}", exercise_2(&scenario));
 }
+
+aoc_2023::check_test!(check);
@@ -1,3 +1,4 @@
+use aoc_2023::check::{shrink_usize, Checker};
 use std::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -18,7 +19,7 @@ impl FromStr for Scenario {
                 .next()
                 .ok_or("missing field")?
                 .split(':')
-                .last()
+                .next_back()
                 .unwrap()
                 .split_whitespace()
                 .map(|s| s.parse::<u32>().unwrap() as usize)
@@ -66,31 +67,71 @@ fn exercise1(scenario: &Scenario) -> usize {
     res
 }
 
-fn exercise2() -> usize {
-    let time = 46_689_866; // hard-coded from input_6.txt
-    let max_distance = 358_105_418_071_080;
-
-    let mut min_speed = 0;
-
-    let mut delta = time / 2;
-    let mut speed = delta;
-    while delta > 0 {
+/// The distance grows with the speed up to half the time, so the slowest winning speed is
+/// found by binary search in `0..=time / 2`. The winning speeds are symmetric around it.
+/// The race must be winnable.
+fn exercise2(time: usize, max_distance: usize) -> usize {
+    let (mut low, mut high) = (0, time / 2);
+    while low < high {
+        let speed = (low + high) / 2;
         let t = time - speed;
         let d = speed * t;
         if d > max_distance {
-            min_speed = speed;
-            speed -= delta;
+            high = speed;
         } else {
-            speed += delta;
+            low = speed + 1;
         }
-        delta /= 2;
     }
+    let min_speed = low;
 
-    time - 2 * (min_speed - 1) + 1
+    time - 2 * min_speed + 1
+}
+
+/// Ways of beating the record, trying every speed
+fn brute_force(time: usize, max_distance: usize) -> usize {
+    (1..time)
+        .filter(|speed| speed * (time - speed) > max_distance)
+        .count()
+}
+
+/// Compares the binary search of exercise 2 with the brute force, on races that can be won
+fn check(checker: &Checker) -> bool {
+    let best = |time: usize| (time / 2) * (time - time / 2);
+    let winnable = |&(time, max_distance): &(usize, usize)| time >= 2 && max_distance < best(time);
+    checker.check(
+        "exercise 2",
+        |rng| {
+            let time = rng.range(2..300);
+            (time, rng.range(0..best(time)))
+        },
+        |&(time, max_distance)| {
+            let shorter = shrink_usize(time).into_iter().map(|t| (t, max_distance));
+            let closer = shrink_usize(max_distance).into_iter().map(|d| (time, d));
+            let mut res = shorter.chain(closer).collect::<Vec<_>>();
+            res.retain(winnable);
+            res
+        },
+        |&(time, max_distance)| {
+            let (fast, slow) = (
+                exercise2(time, max_distance),
+                brute_force(time, max_distance),
+            );
+            match fast == slow {
+                true => Ok(()),
+                false => Err(format!("binary search {}, brute force {}", fast, slow)),
+            }
+        },
+    )
 }
 
 fn main() {
+    // `--check [cases] [seed]` compares exercise 2 with a brute force instead
+    Checker::run_from_args(check);
+
     let scenario = parse_input("data/6_input.txt");
     println!("exercise 1: {}", exercise1(&scenario));
-    println!("exercise 2: {}", exercise2());
+    // hard-coded from input_6.txt
+    println!("exercise 2: {}", exercise2(46_689_866, 358_105_418_071_080));
 }
+
+aoc_2023::check_test!(check);
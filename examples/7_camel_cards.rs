@@ -1,3 +1,4 @@
+use aoc_2023::check::Checker;
 use std::char;
 use std::cmp::PartialOrd;
 use std::collections::HashMap;
@@ -245,7 +246,7 @@ fn exercise1(hands: &[Hand]) -> usize {
     ] {
         if let Some(hands) = hand_types.get(&hand) {
             let mut sorted_hands = hands.to_vec();
-            sorted_hands.sort_by(|a, b| a.cards.cmp(&b.cards));
+            sorted_hands.sort_by_key(|a| a.cards);
             sorted.extend(sorted_hands);
         }
     }
@@ -278,7 +279,7 @@ fn exercise2(hands: &[Hand2]) -> usize {
     ] {
         if let Some(hands) = hand_types.get(&hand) {
             let mut sorted_hands = hands.to_vec();
-            sorted_hands.sort_by(|a, b| a.cards.cmp(&b.cards));
+            sorted_hands.sort_by_key(|a| a.cards);
             sorted.extend(sorted_hands);
         }
     }
@@ -291,10 +292,90 @@ fn exercise2(hands: &[Hand2]) -> usize {
     res
 }
 
+/// Hand type from the sorted card counts alone
+fn classify(cards: &[Card; 5]) -> HandType {
+    let mut counts = HashMap::new();
+    for card in cards.iter() {
+        *counts.entry(*card).or_insert(0) += 1;
+    }
+    let mut counts = counts.into_values().collect::<Vec<usize>>();
+    counts.sort_by(|a, b| b.cmp(a));
+    match counts[..] {
+        [5] => HandType::FiveOfAKind,
+        [4, 1] => HandType::FourOfAKind,
+        [3, 2] => HandType::FullHouse,
+        [3, 1, 1] => HandType::ThreeOfAKind,
+        [2, 2, 1] => HandType::TwoPairs,
+        [2, 1, 1, 1] => HandType::Pair,
+        _ => HandType::HighCard,
+    }
+}
+
+/// Best hand type with jokers, trying every card in place of every joker
+fn brute_force(cards: &[Card; 5]) -> HandType {
+    let others = "23456789TQKA".chars().map(Card::from).collect::<Vec<_>>();
+    let mut hands = vec![*cards];
+    for i in (0..5).filter(|&i| cards[i] == Card::Jack) {
+        hands = hands
+            .iter()
+            .flat_map(|hand| {
+                others.iter().map(move |&other| {
+                    let mut hand = *hand;
+                    hand[i] = other;
+                    hand
+                })
+            })
+            .collect();
+    }
+    hands.iter().map(classify).max().unwrap()
+}
+
+/// Compares the joker promotion of exercise 2 with the brute force
+fn check(checker: &Checker) -> bool {
+    let cards = "23456789TJQKA".chars().map(Card::from).collect::<Vec<_>>();
+    checker.check(
+        "joker promotion",
+        |rng| {
+            // repeated cards and jokers are what matters, so make them common
+            let mut hand = [*rng.choose(&cards); 5];
+            for i in 1..5 {
+                hand[i] = match rng.range(0..3) {
+                    0 => Card::Jack,
+                    1 => hand[rng.range(0..i)],
+                    _ => *rng.choose(&cards),
+                };
+            }
+            hand
+        },
+        |hand| {
+            // the same hand with one card replaced by a two
+            let changed = (0..5).filter(|&i| hand[i] != Card::Two).map(|i| {
+                let mut hand = *hand;
+                hand[i] = Card::Two;
+                hand
+            });
+            changed.collect()
+        },
+        |hand| {
+            let jokers = hand.map(Card2::from);
+            let (fast, slow) = (HandType::from(&jokers), brute_force(hand));
+            match fast == slow {
+                true => Ok(()),
+                false => Err(format!("promoted to {:?}, brute force {:?}", fast, slow)),
+            }
+        },
+    )
+}
+
 fn main() {
+    // `--check [cases] [seed]` compares the joker promotion with a brute force instead
+    Checker::run_from_args(check);
+
     let hands = parse_input("data/7_input.txt");
     println!("exercise 1: {}", exercise1(&hands));
 
     let hands: Vec<Hand2> = hands.iter().map(|h| h.into()).collect();
     println!("exercise 2: {}", exercise2(&hands));
 }
+
+aoc_2023::check_test!(check);
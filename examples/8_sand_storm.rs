@@ -44,7 +44,8 @@ fn exercise1(scenario: &Scenario) -> usize {
     steps
 }
 
-fn exercise2(scenario: &Scenario) -> usize {
+/// `None` if the answer does not fit in a `u128`
+fn exercise2(scenario: &Scenario) -> Option<u128> {
     let mut destinations = Vec::new();
     for origin in scenario.nodes.keys().filter(|&s| s.ends_with('A')) {
         println!("origin: {}", origin);
@@ -65,11 +66,16 @@ fn exercise2(scenario: &Scenario) -> usize {
     }
     println!("destinations: {:?}", destinations);
 
-    destinations.into_iter().fold(1, lcm)
+    destinations
+        .into_iter()
+        .try_fold(1, |acc, steps| lcm(acc, steps as u128))
 }
 
 fn main() {
     let scenario = parse_input("data/8_input.txt");
     println!("exercise1: {}", exercise1(&scenario));
-    println!("exercise2: {}", exercise2(&scenario));
+    match exercise2(&scenario) {
+        Some(res) => println!("exercise2: {}", res),
+        None => println!("exercise2: overflow"),
+    }
 }
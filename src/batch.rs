@@ -0,0 +1,225 @@
+//! Fast evaluation of many parts against the workflows of day 19.
+//!
+//! Workflows are compiled into a flat program where every rule is an instruction and
+//! targets are instruction offsets, so evaluating a part involves no name lookups.
+
+use crate::workflow::{Diagnostic, Op, Part, Schema, Target, WorkflowId, Workflows};
+use std::io::BufRead;
+
+/// Where to go when the condition of an instruction holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Jump {
+    Accept,
+    Reject,
+    /// First instruction of a workflow, and the workflow itself for the hit counters
+    Workflow {
+        offset: usize,
+        id: WorkflowId,
+    },
+}
+
+/// A rule: `part[attr] op value`. Instructions without condition always jump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Instruction {
+    condition: Option<(usize, Op, usize)>,
+    jump: Jump,
+}
+
+/// Parts evaluated in a batch
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchResult {
+    pub parts: usize,
+    pub accepted: usize,
+    /// Sum of the ratings of the accepted parts
+    pub accepted_rating: u64,
+    /// Number of parts that went through each workflow, indexed by [`WorkflowId`]
+    pub hits: Vec<u64>,
+}
+
+impl BatchResult {
+    fn new(n_workflows: usize) -> Self {
+        Self {
+            hits: vec![0; n_workflows],
+            ..Default::default()
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.parts += other.parts;
+        self.accepted += other.accepted;
+        self.accepted_rating += other.accepted_rating;
+        for (hits, other) in self.hits.iter_mut().zip(other.hits.iter()) {
+            *hits += other;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BatchError {
+    Io(std::io::Error),
+    Parse(Vec<Diagnostic>),
+}
+
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Parse(diagnostics) => {
+                let diagnostics = diagnostics.iter().map(|d| d.to_string());
+                write!(f, "{}", diagnostics.collect::<Vec<_>>().join("\n"))
+            }
+        }
+    }
+}
+
+/// Lines read from the stream before evaluating them
+const CHUNK_LINES: usize = 1 << 16;
+
+#[derive(Debug, Clone)]
+pub struct Program {
+    code: Vec<Instruction>,
+    start: Jump,
+    schema: Schema,
+    n_workflows: usize,
+    threads: usize,
+}
+
+impl Program {
+    pub fn compile(workflows: &Workflows) -> Self {
+        let mut offsets = Vec::new();
+        let mut offset = 0;
+        for workflow in workflows.workflows.iter() {
+            offsets.push(offset);
+            offset += workflow.rules.len();
+        }
+        let jump = |target: Target| match target {
+            Target::Accept => Jump::Accept,
+            Target::Reject => Jump::Reject,
+            Target::Workflow(id) => Jump::Workflow {
+                offset: offsets[id.0],
+                id,
+            },
+        };
+        let code = workflows
+            .workflows
+            .iter()
+            .flat_map(|w| w.rules.iter())
+            .map(|rule| Instruction {
+                condition: rule.condition.map(|c| (c.attr, c.op, c.value)),
+                jump: jump(rule.target),
+            })
+            .collect();
+        Self {
+            code,
+            start: jump(Target::Workflow(workflows.start)),
+            schema: workflows.schema.clone(),
+            n_workflows: workflows.workflows.len(),
+            threads: 1,
+        }
+    }
+
+    /// Number of threads used to evaluate each batch
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Whether the part is accepted, counting the workflows it goes through
+    fn run(&self, part: &Part, hits: &mut [u64]) -> bool {
+        let mut jump = self.start;
+        loop {
+            let mut pc = match jump {
+                Jump::Accept => return true,
+                Jump::Reject => return false,
+                Jump::Workflow { offset, id } => {
+                    hits[id.0] += 1;
+                    offset
+                }
+            };
+            // workflows end with a default rule, so this always finds a jump
+            loop {
+                let instruction = &self.code[pc];
+                match instruction.condition {
+                    Some((attr, op, value)) if !op.eval(part.0[attr], value) => pc += 1,
+                    _ => break,
+                }
+            }
+            jump = self.code[pc].jump;
+        }
+    }
+
+    fn evaluate_serial(&self, parts: &[Part]) -> BatchResult {
+        let mut res = BatchResult::new(self.n_workflows);
+        for part in parts {
+            res.parts += 1;
+            if self.run(part, &mut res.hits) {
+                res.accepted += 1;
+                res.accepted_rating += part.rating() as u64;
+            }
+        }
+        res
+    }
+
+    /// Splits the items between the threads and merges their results.
+    /// `evaluate` receives a slice of items and the position of its first item.
+    fn parallel<T: Sync, E: Send>(
+        &self,
+        items: &[T],
+        evaluate: impl Fn(&[T], usize) -> Result<BatchResult, E> + Sync,
+    ) -> Result<BatchResult, E> {
+        if self.threads == 1 || items.len() < 2 * self.threads {
+            return evaluate(items, 0);
+        }
+        let chunk = items.len().div_ceil(self.threads);
+        let mut res = BatchResult::new(self.n_workflows);
+        let evaluate = &evaluate;
+        std::thread::scope(|scope| {
+            let handles = items
+                .chunks(chunk)
+                .enumerate()
+                .map(|(i, items)| scope.spawn(move || evaluate(items, i * chunk)))
+                .collect::<Vec<_>>();
+            for handle in handles {
+                res.merge(&handle.join().expect("evaluation thread panicked")?);
+            }
+            Ok(res)
+        })
+    }
+
+    /// Evaluates parts already in memory
+    pub fn evaluate(&self, parts: &[Part]) -> BatchResult {
+        let res = self.parallel(parts, |parts, _| Ok::<_, ()>(self.evaluate_serial(parts)));
+        res.unwrap()
+    }
+
+    /// Evaluates the parts of a stream, one per line, in bounded memory.
+    /// Empty lines are skipped.
+    pub fn evaluate_reader<R: BufRead>(&self, reader: R) -> Result<BatchResult, BatchError> {
+        let mut res = BatchResult::new(self.n_workflows);
+        let mut lines = Vec::with_capacity(CHUNK_LINES);
+        let mut first_line = 1;
+        let mut evaluate = |lines: &[String], first_line: usize| {
+            let chunk = self.parallel(lines, |lines, offset| {
+                let mut parts = Vec::with_capacity(lines.len());
+                for (i, line) in lines.iter().enumerate() {
+                    if !line.is_empty() {
+                        parts.push(self.schema.parse_part(line, first_line + offset + i)?);
+                    }
+                }
+                Ok(self.evaluate_serial(&parts))
+            });
+            res.merge(&chunk.map_err(BatchError::Parse)?);
+            Ok(())
+        };
+        for line in reader.lines() {
+            lines.push(line.map_err(BatchError::Io)?);
+            if lines.len() == CHUNK_LINES {
+                evaluate(&lines, first_line)?;
+                first_line += lines.len();
+                lines.clear();
+            }
+        }
+        evaluate(&lines, first_line)?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,46 @@
+use aoc_2023::generate;
+use aoc_2023::rng::Rng;
+use std::str::FromStr;
+
+/// Value of an option, or exits if it is missing or invalid
+fn value<T: FromStr>(name: &str, value: Option<&String>) -> T {
+    value.and_then(|v| v.parse().ok()).unwrap_or_else(|| {
+        eprintln!("invalid value for {}", name);
+        std::process::exit(1);
+    })
+}
+
+/// Prints a random input for a day, and its answers on stderr when they are known
+fn main() {
+    // `generate <day> [--size N] [--seed N] [--answers]`
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let day = value("the day", args.first());
+    let (mut size, mut seed, mut answers) = (20, 0, false);
+    let mut options = args.iter().skip(1);
+    while let Some(arg) = options.next() {
+        match arg.as_str() {
+            "--size" => size = value(arg, options.next()),
+            "--seed" => seed = value(arg, options.next()),
+            "--answers" => answers = true,
+            _ => {
+                eprintln!("unknown argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let mut rng = Rng::new(seed);
+    let Some(generated) = generate::generate(day, size, &mut rng) else {
+        eprintln!("no generator for day {}", day);
+        std::process::exit(1);
+    };
+    print!("{}", generated.input);
+    if answers {
+        for (i, answer) in generated.answers.iter().enumerate() {
+            match answer {
+                Some(answer) => eprintln!("exercise {}: {}", i + 1, answer),
+                None => eprintln!("exercise {}: unknown", i + 1),
+            }
+        }
+    }
+}
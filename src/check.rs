@@ -0,0 +1,161 @@
+//! Property checks: a fast solution is run on many small random inputs and compared
+//! with a naive reference. Failing inputs are shrunk to a minimal counterexample.
+
+use crate::rng::Rng;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+
+/// How many random inputs to try, and from which seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checker {
+    pub cases: usize,
+    pub seed: u64,
+    /// Limit on the shrinking steps, in case shrinking keeps finding larger inputs
+    pub max_shrinks: usize,
+}
+
+/// 200 cases from seed 0
+impl Default for Checker {
+    fn default() -> Self {
+        Self::new(200, 0)
+    }
+}
+
+impl Checker {
+    pub fn new(cases: usize, seed: u64) -> Self {
+        Self {
+            cases,
+            seed,
+            max_shrinks: 1000,
+        }
+    }
+
+    /// Reads `--check [cases] [seed]` from the arguments, without the program name.
+    /// `None` if the first one is not `--check`.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        if args.first().is_none_or(|arg| arg != "--check") {
+            return None;
+        }
+        let default = Self::default();
+        let arg = |i: usize, default: u64| {
+            let value = args.get(i).map(|arg| arg.parse().expect("invalid number"));
+            value.unwrap_or(default)
+        };
+        let cases = arg(1, default.cases as u64) as usize;
+        Some(Self::new(cases, arg(2, default.seed)))
+    }
+
+    /// When the program was started with `--check [cases] [seed]`, runs `check` with
+    /// those and exits with its outcome. Otherwise returns, and the program goes on.
+    pub fn run_from_args(check: impl FnOnce(&Self) -> bool) {
+        let args = std::env::args().skip(1).collect::<Vec<_>>();
+        if let Some(checker) = Self::from_args(&args) {
+            std::process::exit(if check(&checker) { 0 } else { 1 });
+        }
+    }
+
+    /// Checks `property` on random inputs from `generate`. On failure, the input is
+    /// replaced by the first of its `shrink` candidates that still fails, until none does.
+    /// Panics count as failures. Prints the outcome and returns whether all cases passed.
+    pub fn check<T, G, S, P>(&self, name: &str, mut generate: G, shrink: S, property: P) -> bool
+    where
+        T: Clone + Debug,
+        G: FnMut(&mut Rng) -> T,
+        S: Fn(&T) -> Vec<T>,
+        P: Fn(&T) -> Result<(), String>,
+    {
+        let mut rng = Rng::new(self.seed);
+        // panics are expected while shrinking, so their messages are only reported once
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let run = |value: &T| {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| property(value)));
+            result.unwrap_or_else(|payload| {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned());
+                Err(format!("panicked: {}", message.unwrap_or_default()))
+            })
+        };
+
+        let mut failure = None;
+        for case in 1..=self.cases {
+            let value = generate(&mut rng);
+            if let Err(error) = run(&value) {
+                failure = Some((case, value, error));
+                break;
+            }
+        }
+        let Some((case, mut value, mut error)) = failure else {
+            panic::set_hook(hook);
+            println!("{}: ok, {} cases", name, self.cases);
+            return true;
+        };
+
+        let mut shrinks = 0;
+        while shrinks < self.max_shrinks {
+            let smaller = shrink(&value)
+                .into_iter()
+                .find_map(|candidate| run(&candidate).err().map(|e| (candidate, e)));
+            let Some((candidate, candidate_error)) = smaller else {
+                break;
+            };
+            (value, error) = (candidate, candidate_error);
+            shrinks += 1;
+        }
+        panic::set_hook(hook);
+        println!(
+            "{}: failed on case {} (seed {}), shrunk {} times to\n  {:?}\n  {}",
+            name, case, self.seed, shrinks, value, error
+        );
+        false
+    }
+}
+
+/// Smaller values to try instead of `n`, closest to zero first
+pub fn shrink_usize(n: usize) -> Vec<usize> {
+    let mut res = vec![0, n / 2, n.saturating_sub(1)];
+    res.dedup();
+    res.retain(|&m| m < n);
+    res
+}
+
+/// Shorter vectors first (without each half, then without each item), then the same
+/// vector with one item shrunk
+pub fn shrink_vec<T: Clone>(items: &[T], shrink_item: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut res = Vec::new();
+    if items.len() > 1 {
+        let half = items.len() / 2;
+        res.push(items[half..].to_vec());
+        res.push(items[..half].to_vec());
+    }
+    for i in 0..items.len() {
+        let mut shorter = items.to_vec();
+        shorter.remove(i);
+        res.push(shorter);
+    }
+    for (i, item) in items.iter().enumerate() {
+        for smaller in shrink_item(item) {
+            let mut changed = items.to_vec();
+            changed[i] = smaller;
+            res.push(changed);
+        }
+    }
+    res
+}
+
+/// Test that runs `$check`, a `fn(&Checker) -> bool`, with the default cases, so that
+/// `cargo test` covers it
+#[macro_export]
+macro_rules! check_test {
+    ($check:ident) => {
+        #[cfg(test)]
+        mod check_test {
+            #[test]
+            fn passes() {
+                assert!(super::$check(&$crate::check::Checker::default()));
+            }
+        }
+    };
+}
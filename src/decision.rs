@@ -0,0 +1,184 @@
+//! Decision trees compiled from the workflows of day 19: every path from the root to a
+//! leaf is a sequence of conditions, and each leaf is a hyper-rectangle of parts that
+//! are all accepted or all rejected.
+
+use crate::workflow::{Condition, PartRange, Target, WorkflowId, Workflows};
+use std::fmt::Write;
+
+/// Parts that reach the same verdict, with the rules that sent them there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leaf {
+    pub accepted: bool,
+    pub range: PartRange,
+    /// Matching rules, as `(workflow, rule index)`, from the `in` workflow to the verdict
+    pub path: Vec<(WorkflowId, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Leaf(Leaf),
+    /// Test of the given rule. Each side may have several nodes, as `==` and `!=`
+    /// split a range in two pieces.
+    Branch {
+        at: (WorkflowId, usize),
+        condition: Condition,
+        pass: Vec<Node>,
+        fail: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecisionTree {
+    pub roots: Vec<Node>,
+}
+
+impl DecisionTree {
+    /// Compiles the workflows, skipping the tests whose outcome is already known
+    pub fn compile(workflows: &Workflows) -> Self {
+        let range = PartRange::new(&workflows.schema);
+        Self {
+            roots: compile_rule(workflows, range, workflows.start, 0, &[]),
+        }
+    }
+
+    pub fn leaves(&self) -> Vec<&Leaf> {
+        let mut res = Vec::new();
+        let mut stack = self.roots.iter().rev().collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Leaf(leaf) => res.push(leaf),
+                Node::Branch { pass, fail, .. } => {
+                    stack.extend(fail.iter().rev());
+                    stack.extend(pass.iter().rev());
+                }
+            }
+        }
+        res
+    }
+
+    /// Number of accepted parts
+    pub fn accepted_volume(&self) -> u128 {
+        let accepted = self.leaves().into_iter().filter(|leaf| leaf.accepted);
+        accepted.map(|leaf| leaf.range.len()).sum()
+    }
+
+    /// One line per leaf: verdict, range and the rules that lead to it
+    pub fn format_leaves(&self, workflows: &Workflows) -> String {
+        let mut res = String::new();
+        for leaf in self.leaves() {
+            let path = leaf
+                .path
+                .iter()
+                .map(|&(id, rule)| format!("{}#{}", workflows[id].name, rule));
+            writeln!(
+                res,
+                "{} {} via {}",
+                if leaf.accepted { "A" } else { "R" },
+                workflows.schema.format_range(&leaf.range),
+                path.collect::<Vec<_>>().join(" -> ")
+            )
+            .unwrap();
+        }
+        res
+    }
+
+    /// Graphviz description of the tree
+    pub fn to_dot(&self, workflows: &Workflows) -> String {
+        let mut res = String::from("digraph decision_tree {\n");
+        writeln!(res, "  root [shape=point];").unwrap();
+        let mut next_id = 0;
+        // (node, parent, edge label)
+        let mut stack = self
+            .roots
+            .iter()
+            .map(|node| (node, "root".to_string(), ""))
+            .collect::<Vec<_>>();
+        while let Some((node, parent, label)) = stack.pop() {
+            let name = format!("n{}", next_id);
+            next_id += 1;
+            match node {
+                Node::Leaf(leaf) => {
+                    let (verdict, color) = match leaf.accepted {
+                        true => ("A", "palegreen"),
+                        false => ("R", "lightpink"),
+                    };
+                    let range = workflows.schema.format_range(&leaf.range);
+                    writeln!(
+                        res,
+                        "  {} [shape=box, style=filled, fillcolor={}, label=\"{}\\n{}\"];",
+                        name, color, verdict, range
+                    )
+                    .unwrap();
+                }
+                Node::Branch {
+                    at: (id, rule),
+                    condition,
+                    pass,
+                    fail,
+                } => {
+                    writeln!(
+                        res,
+                        "  {} [label=\"{}#{}\\n{}\"];",
+                        name,
+                        workflows[*id].name,
+                        rule,
+                        workflows.format_condition(condition)
+                    )
+                    .unwrap();
+                    stack.extend(fail.iter().map(|n| (n, name.clone(), "no")));
+                    stack.extend(pass.iter().map(|n| (n, name.clone(), "yes")));
+                }
+            }
+            writeln!(res, "  {} -> {} [label=\"{}\"];", parent, name, label).unwrap();
+        }
+        res.push_str("}\n");
+        res
+    }
+}
+
+fn compile_rule(
+    workflows: &Workflows,
+    range: PartRange,
+    id: WorkflowId,
+    rule_index: usize,
+    path: &[(WorkflowId, usize)],
+) -> Vec<Node> {
+    let rule = &workflows[id].rules[rule_index];
+    let (pass, fail) = range.split(&rule.condition);
+    let mut matched = path.to_vec();
+    matched.push((id, rule_index));
+
+    let pass = pass
+        .into_iter()
+        .flat_map(|range| compile_target(workflows, range, rule.target, &matched))
+        .collect::<Vec<_>>();
+    let fail = fail
+        .into_iter()
+        .flat_map(|range| compile_rule(workflows, range, id, rule_index + 1, path))
+        .collect::<Vec<_>>();
+    match rule.condition {
+        Some(condition) if !pass.is_empty() && !fail.is_empty() => vec![Node::Branch {
+            at: (id, rule_index),
+            condition,
+            pass,
+            fail,
+        }],
+        _ => pass.into_iter().chain(fail).collect(),
+    }
+}
+
+fn compile_target(
+    workflows: &Workflows,
+    range: PartRange,
+    target: Target,
+    path: &[(WorkflowId, usize)],
+) -> Vec<Node> {
+    match target {
+        Target::Workflow(next) => compile_rule(workflows, range, next, 0, path),
+        verdict => vec![Node::Leaf(Leaf {
+            accepted: verdict == Target::Accept,
+            range,
+            path: path.to_vec(),
+        })],
+    }
+}
@@ -0,0 +1,1449 @@
+//! Random puzzle inputs of any size, for load testing and fuzzing the solutions.
+//!
+//! Every generator builds a valid input for its day from a [`Rng`] and a size parameter
+//! (lines, grid side, ... depending on the day). When the answers follow from the way the
+//! input is built, they are returned too.
+
+use crate::lcm;
+use crate::rng::Rng;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generated {
+    pub input: String,
+    /// Answers of both parts, when known
+    pub answers: [Option<i128>; 2],
+}
+
+impl Generated {
+    fn new(input: String, part1: Option<i128>, part2: Option<i128>) -> Self {
+        Self {
+            input,
+            answers: [part1, part2],
+        }
+    }
+}
+
+/// Generates an input for the given day, or `None` if there is no such day
+pub fn generate(day: usize, size: usize, rng: &mut Rng) -> Option<Generated> {
+    let generator: fn(usize, &mut Rng) -> Generated = match day {
+        1 => trebuchet,
+        2 => cubes,
+        3 => schematic,
+        4 => scratchcards,
+        5 => almanac,
+        6 => races,
+        7 => camel_cards,
+        8 => network,
+        9 => sequences,
+        10 => pipes,
+        11 => galaxies,
+        12 => springs,
+        13 => mirrors,
+        14 => platform,
+        15 => init_sequence,
+        16 => contraption,
+        17 => heat_map,
+        18 => dig_plan,
+        19 => workflows,
+        20 => modules,
+        21 => garden,
+        22 => bricks,
+        23 => trails,
+        24 => hailstones,
+        25 => wiring,
+        _ => return None,
+    };
+    Some(generator(size, rng))
+}
+
+/// Shortest name length, at least `min`, with room for twice `count` names, so that
+/// [`unique_name`] finds an unused one in a couple of tries
+fn name_len(count: usize, min: usize) -> usize {
+    let mut len = min;
+    while 26usize.pow(len as u32) < 2 * count {
+        len += 1;
+    }
+    len
+}
+
+/// Random string of lowercase letters, different from all the ones already used. Loops
+/// forever if every name of that length is used: see [`name_len`].
+fn unique_name(rng: &mut Rng, len: usize, used: &mut HashSet<String>) -> String {
+    loop {
+        let name = (0..len)
+            .map(|_| (b'a' + rng.range(0..26) as u8) as char)
+            .collect::<String>();
+        if used.insert(name.clone()) {
+            return name;
+        }
+    }
+}
+
+const DIGIT_WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Day 1: `size` lines. Filler letters never appear in a digit word, so words cannot overlap.
+fn trebuchet(size: usize, rng: &mut Rng) -> Generated {
+    let filler = b"abcdjklmpqyz";
+    let (mut input, mut part1, mut part2) = (String::new(), 0, 0);
+    for _ in 0..size.max(1) {
+        let n_tokens = rng.range(1..7);
+        let digit = rng.range(0..n_tokens);
+        // (value, spelled out)
+        let tokens = (0..n_tokens)
+            .map(|i| (rng.range(1..10), i != digit && rng.chance(0.5)))
+            .collect::<Vec<_>>();
+        for &(value, spelled) in tokens.iter() {
+            for _ in 0..rng.range(1..4) {
+                input.push(*rng.choose(filler) as char);
+            }
+            match spelled {
+                true => input.push_str(DIGIT_WORDS[value - 1]),
+                false => write!(input, "{}", value).unwrap(),
+            }
+        }
+        if rng.chance(0.5) {
+            input.push(*rng.choose(filler) as char);
+        }
+        input.push('\n');
+
+        let digits = tokens.iter().filter(|(_, spelled)| !spelled);
+        let digits = digits.map(|(value, _)| *value).collect::<Vec<_>>();
+        part1 += digits[0] * 10 + digits[digits.len() - 1];
+        part2 += tokens[0].0 * 10 + tokens[n_tokens - 1].0;
+    }
+    Generated::new(input, Some(part1 as i128), Some(part2 as i128))
+}
+
+/// Day 2: `size` games
+fn cubes(size: usize, rng: &mut Rng) -> Generated {
+    let colors = ["red", "green", "blue"];
+    let limits = [12, 13, 14];
+    let (mut input, mut part1, mut part2) = (String::new(), 0, 0);
+    for game in 1..=size.max(1) {
+        let mut max = [0; 3];
+        let reveals = (0..rng.range(1..7))
+            .map(|_| {
+                let mut shown = (0..3).filter(|_| rng.chance(0.6)).collect::<Vec<_>>();
+                if shown.is_empty() {
+                    shown.push(rng.range(0..3));
+                }
+                rng.shuffle(&mut shown);
+                let counts = shown.iter().map(|&color| {
+                    let count = rng.range(1..20);
+                    max[color] = max[color].max(count);
+                    format!("{} {}", count, colors[color])
+                });
+                counts.collect::<Vec<_>>().join(", ")
+            })
+            .collect::<Vec<_>>();
+        writeln!(input, "Game {}: {}", game, reveals.join("; ")).unwrap();
+        if (0..3).all(|color| max[color] <= limits[color]) {
+            part1 += game;
+        }
+        part2 += max.iter().product::<usize>();
+    }
+    Generated::new(input, Some(part1 as i128), Some(part2 as i128))
+}
+
+/// Day 3: a `size` x `size` schematic
+fn schematic(size: usize, rng: &mut Rng) -> Generated {
+    let side = size.max(3);
+    let symbols = b"*#+$@/=%&-";
+    let mut grid = vec![vec![b'.'; side]; side];
+    // (row, first column, last column, value)
+    let mut numbers = Vec::new();
+    for (row, line) in grid.iter_mut().enumerate() {
+        let mut col = 0;
+        while col < side {
+            let len = rng.range(1..4);
+            if rng.chance(0.15) && col + len <= side {
+                let value = rng.range(10usize.pow(len as u32 - 1)..10usize.pow(len as u32));
+                line[col..col + len].copy_from_slice(value.to_string().as_bytes());
+                numbers.push((row, col, col + len - 1, value));
+                // numbers are separated by at least one other tile
+                col += len + 1;
+            } else {
+                if rng.chance(0.08) {
+                    line[col] = *rng.choose(symbols);
+                }
+                col += 1;
+            }
+        }
+    }
+
+    let symbol = |r: usize, c: usize| !grid[r][c].is_ascii_digit() && grid[r][c] != b'.';
+    let (mut part1, mut gears) = (0, HashMap::new());
+    for &(row, first, last, value) in numbers.iter() {
+        let mut adjacent = Vec::new();
+        for r in row.saturating_sub(1)..=(row + 1).min(side - 1) {
+            for c in first.saturating_sub(1)..=(last + 1).min(side - 1) {
+                if symbol(r, c) {
+                    adjacent.push((r, c));
+                }
+            }
+        }
+        if !adjacent.is_empty() {
+            part1 += value;
+        }
+        for (r, c) in adjacent.into_iter().filter(|&(r, c)| grid[r][c] == b'*') {
+            gears.entry((r, c)).or_insert(Vec::new()).push(value);
+        }
+    }
+    let part2 = gears
+        .values()
+        .filter(|values| values.len() == 2)
+        .map(|values| values[0] * values[1])
+        .sum::<usize>();
+
+    let input = grid
+        .iter()
+        .map(|line| String::from_utf8_lossy(line).to_string() + "\n")
+        .collect();
+    Generated::new(input, Some(part1 as i128), Some(part2 as i128))
+}
+
+/// Day 4: `size` cards
+fn scratchcards(size: usize, rng: &mut Rng) -> Generated {
+    let n_cards = size.max(1);
+    let mut input = String::new();
+    let mut matches = Vec::new();
+    for card in 1..=n_cards {
+        let mut numbers = (1..100).collect::<Vec<usize>>();
+        rng.shuffle(&mut numbers);
+        let (winning, others) = numbers.split_at(10);
+        let n_matches = if rng.chance(0.5) { 0 } else { rng.range(0..11) };
+        let mut mine = winning[..n_matches].to_vec();
+        mine.extend(&others[..25 - n_matches]);
+        rng.shuffle(&mut mine);
+        let format = |numbers: &[usize]| {
+            let numbers = numbers.iter().map(|n| format!("{:2}", n));
+            numbers.collect::<Vec<_>>().join(" ")
+        };
+        writeln!(
+            input,
+            "Card {:3}: {} | {}",
+            card,
+            format(winning),
+            format(&mine)
+        )
+        .unwrap();
+        matches.push(n_matches);
+    }
+
+    let part1 = matches
+        .iter()
+        .filter(|&&m| m > 0)
+        .map(|&m| 1 << (m - 1))
+        .sum::<usize>();
+    let mut copies = vec![1; n_cards];
+    for (i, &m) in matches.iter().enumerate() {
+        for j in i + 1..(i + 1 + m).min(n_cards) {
+            copies[j] += copies[i];
+        }
+    }
+    let part2 = copies.iter().sum::<usize>();
+    Generated::new(input, Some(part1 as i128), Some(part2 as i128))
+}
+
+/// Day 5: `size` seed ranges, through seven maps. Each map moves disjoint source ranges to
+/// disjoint destination ranges, so the ranges of seeds can be followed exactly.
+fn almanac(size: usize, rng: &mut Rng) -> Generated {
+    const MAX: usize = u32::MAX as usize;
+    let names = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+    let mut seeds = Vec::new();
+    for _ in 0..size.max(1) {
+        let start = rng.range(0..MAX / 2);
+        seeds.push(start);
+        seeds.push(rng.range(1..MAX / 32));
+    }
+    let mut input = format!(
+        "seeds: {}\n",
+        seeds
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+
+    // mapping of each stage: (destination, source, length)
+    let mut stages = Vec::new();
+    for stage in names.windows(2) {
+        let mut cuts = (0..rng.range(4..40))
+            .map(|_| rng.range(0..MAX))
+            .collect::<Vec<_>>();
+        cuts.push(0);
+        cuts.push(MAX);
+        cuts.sort();
+        cuts.dedup();
+        let mut sources = cuts
+            .windows(2)
+            .filter(|_| rng.chance(0.7))
+            .map(|w| (w[0], w[1] - w[0]))
+            .collect::<Vec<_>>();
+        let total = sources.iter().map(|(_, len)| len).sum::<usize>();
+        rng.shuffle(&mut sources);
+        let mut destination = rng.range(0..MAX - total + 1);
+        let mut mapping = Vec::new();
+        for (source, len) in sources {
+            mapping.push((destination, source, len));
+            destination += len;
+        }
+
+        writeln!(input, "\n{}-to-{} map:", stage[0], stage[1]).unwrap();
+        for &(destination, source, len) in mapping.iter() {
+            writeln!(input, "{} {} {}", destination, source, len).unwrap();
+        }
+        stages.push(mapping);
+    }
+
+    let location = |seed: usize| {
+        stages.iter().fold(seed, |value, mapping| {
+            let found = mapping
+                .iter()
+                .find(|&&(_, source, len)| (source..source + len).contains(&value));
+            found.map_or(value, |&(destination, source, _)| {
+                destination + value - source
+            })
+        })
+    };
+    let part1 = seeds.iter().map(|&s| location(s)).min();
+
+    // seed ranges as `start..end`, split wherever a source range starts or ends
+    let mut ranges = seeds
+        .chunks(2)
+        .map(|c| (c[0], c[0] + c[1]))
+        .collect::<Vec<_>>();
+    for mapping in stages.iter() {
+        let mut mapped = Vec::new();
+        while let Some((start, end)) = ranges.pop() {
+            let overlap = mapping
+                .iter()
+                .find(|&&(_, source, len)| start.max(source) < end.min(source + len));
+            let Some(&(destination, source, len)) = overlap else {
+                mapped.push((start, end));
+                continue;
+            };
+            let (low, high) = (start.max(source), end.min(source + len));
+            mapped.push((destination + low - source, destination + high - source));
+            if start < low {
+                ranges.push((start, low));
+            }
+            if high < end {
+                ranges.push((high, end));
+            }
+        }
+        ranges = mapped;
+    }
+    let part2 = ranges.iter().map(|&(start, _)| start).min();
+    Generated::new(input, part1.map(|p| p as i128), part2.map(|p| p as i128))
+}
+
+/// Ways of beating the record of a race
+fn ways_to_win(time: u128, record: u128) -> u128 {
+    // the distance grows up to half the time, so find the first hold time that wins
+    let (mut low, mut high) = (0, time / 2);
+    if high * (time - high) <= record {
+        return 0;
+    }
+    while low < high {
+        let mid = (low + high) / 2;
+        match mid * (time - mid) > record {
+            true => high = mid,
+            false => low = mid + 1,
+        }
+    }
+    time + 1 - 2 * low
+}
+
+/// Day 6: `size` races, at most four
+fn races(size: usize, rng: &mut Rng) -> Generated {
+    let n_races = size.clamp(1, 4);
+    let mut times = Vec::new();
+    let mut records = Vec::new();
+    for _ in 0..n_races {
+        let time = rng.range(7..100);
+        let best = (time / 2) * (time - time / 2);
+        times.push(time);
+        records.push(rng.range(1..best));
+    }
+    let format = |values: &[usize]| {
+        let values = values.iter().map(|v| format!("{:6}", v));
+        values.collect::<String>()
+    };
+    let input = format!("Time:  {}\nDistance:{}\n", format(&times), format(&records));
+
+    let part1 = times
+        .iter()
+        .zip(records.iter())
+        .map(|(&t, &r)| ways_to_win(t as u128, r as u128))
+        .product::<u128>();
+    let concat = |values: &[usize]| {
+        let digits = values.iter().map(|v| v.to_string()).collect::<String>();
+        digits.parse::<u128>().unwrap()
+    };
+    let part2 = ways_to_win(concat(&times), concat(&records));
+    Generated::new(input, Some(part1 as i128), Some(part2 as i128))
+}
+
+/// Hand type (higher is better) followed by the card values, so hands compare in order.
+/// With jokers, `J` is the weakest card and joins the most repeated other card.
+fn hand_strength(hand: &[u8], jokers: bool) -> (usize, [usize; 5]) {
+    let order = match jokers {
+        true => "J23456789TQKA",
+        false => "23456789TJQKA",
+    };
+    let values = [0, 1, 2, 3, 4].map(|i| order.find(hand[i] as char).unwrap());
+    let mut counts = HashMap::new();
+    for &card in hand.iter() {
+        *counts.entry(card).or_insert(0) += 1;
+    }
+    let n_jokers = match jokers {
+        true => counts.remove(&b'J').unwrap_or(0),
+        false => 0,
+    };
+    let mut counts = counts.into_values().collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.cmp(a));
+    if counts.is_empty() {
+        counts.push(0);
+    }
+    counts[0] += n_jokers;
+    let hand_type = match (counts[0], counts.get(1).copied().unwrap_or(0)) {
+        (5, _) => 6,
+        (4, _) => 5,
+        (3, 2) => 4,
+        (3, _) => 3,
+        (2, 2) => 2,
+        (2, _) => 1,
+        _ => 0,
+    };
+    (hand_type, values)
+}
+
+/// Day 7: `size` different hands
+fn camel_cards(size: usize, rng: &mut Rng) -> Generated {
+    let cards = b"23456789TJQKA";
+    let mut seen = HashSet::new();
+    let mut hands = Vec::new();
+    while hands.len() < size.clamp(1, 100_000) {
+        // reusing cards makes the better hands less rare
+        let mut hand = vec![*rng.choose(cards)];
+        while hand.len() < 5 {
+            let card = match rng.chance(0.3) {
+                true => *rng.choose(&hand),
+                false => *rng.choose(cards),
+            };
+            hand.push(card);
+        }
+        if seen.insert(hand.clone()) {
+            hands.push((hand, rng.range(1..1000)));
+        }
+    }
+    let input = hands
+        .iter()
+        .map(|(hand, bid)| format!("{} {}\n", String::from_utf8_lossy(hand), bid))
+        .collect();
+
+    let winnings = |jokers: bool| {
+        let mut ranked = hands
+            .iter()
+            .map(|(hand, bid)| (hand_strength(hand, jokers), *bid))
+            .collect::<Vec<_>>();
+        ranked.sort();
+        let ranked = ranked.iter().enumerate();
+        ranked.map(|(i, (_, bid))| (i + 1) * bid).sum::<usize>() as i128
+    };
+    Generated::new(input, Some(winnings(false)), Some(winnings(true)))
+}
+
+/// Day 8: directions of length `size` and six ghosts. Each ghost walks a ring whose
+/// length is a different prime and whose `Z` node leads back to the start of the ring.
+fn network(size: usize, rng: &mut Rng) -> Generated {
+    let directions = (0..size.max(1))
+        .map(|_| if rng.chance(0.5) { 'L' } else { 'R' })
+        .collect::<String>();
+    let mut primes = (41..110)
+        .filter(|&n: &usize| (2..n).all(|d| n % d != 0))
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut primes);
+
+    let mut used = HashSet::from(["AAA".to_string(), "ZZZ".to_string()]);
+    let mut name = |rng: &mut Rng, last: u8| loop {
+        let mut name = (0..2)
+            .map(|_| (b'A' + rng.range(0..26) as u8) as char)
+            .collect::<String>();
+        name.push(last as char);
+        if used.insert(name.clone()) {
+            return name;
+        }
+    };
+    let mut nodes = Vec::new();
+    for (ghost, &length) in primes.iter().take(6).enumerate() {
+        let (start, end) = match ghost {
+            0 => ("AAA".to_string(), "ZZZ".to_string()),
+            _ => (name(rng, b'A'), name(rng, b'Z')),
+        };
+        let inner = b"BCDEFGHIJKLMNOPQRSTUVWXY";
+        let mut ring = vec![start];
+        for _ in 1..length {
+            let last = *rng.choose(inner);
+            ring.push(name(rng, last));
+        }
+        ring.push(end);
+        // the end leads back to the second node, so every lap takes `length` steps
+        for i in 0..ring.len() {
+            let next = if i + 1 < ring.len() { i + 1 } else { 1 };
+            nodes.push((ring[i].clone(), ring[next].clone()));
+        }
+    }
+    rng.shuffle(&mut nodes);
+
+    let mut input = format!("{}\n\n", directions);
+    for (node, next) in nodes {
+        writeln!(input, "{} = ({}, {})", node, next, next).unwrap();
+    }
+    let part2 = primes
+        .iter()
+        .take(6)
+        .try_fold(1, |acc, &p| lcm(acc, p as u128));
+    let part2 = part2.and_then(|n| i128::try_from(n).ok());
+    Generated::new(input, Some(primes[0] as i128), part2)
+}
+
+/// Day 9: `size` histories of 21 values of polynomials of degree up to six
+fn sequences(size: usize, rng: &mut Rng) -> Generated {
+    let (mut input, mut part1, mut part2) = (String::new(), 0, 0);
+    for _ in 0..size.max(1) {
+        let degree = rng.range(0..7);
+        let coefficients = (0..=degree)
+            .map(|_| rng.range(0..11) as i128 - 5)
+            .collect::<Vec<_>>();
+        let shift = rng.range(0..10) as i128 - 5;
+        let eval = |x: i128| {
+            let x = x + shift;
+            coefficients.iter().rev().fold(0, |acc, c| acc * x + c)
+        };
+        let values = (0..21).map(|x| eval(x).to_string()).collect::<Vec<_>>();
+        writeln!(input, "{}", values.join(" ")).unwrap();
+        part1 += eval(21);
+        part2 += eval(-1);
+    }
+    Generated::new(input, Some(part1), Some(part2))
+}
+
+/// Random set of cells of a `side` x `side` grid, connected, without holes and without
+/// cells touching only by a corner, so its outline is a simple closed curve
+fn polyomino(side: usize, rng: &mut Rng) -> Vec<Vec<bool>> {
+    let side = side.max(2);
+    let mut cells = vec![vec![false; side]; side];
+    cells[side / 2][side / 2] = true;
+    let mut frontier = vec![(side / 2, side / 2)];
+    let target = side * side / 3;
+    let mut filled = 1;
+    let mut attempts = 0;
+    while filled < target && attempts < 20 * target {
+        attempts += 1;
+        let &(row, col) = rng.choose(&frontier);
+        let (dr, dc) = *rng.choose(&[(0, 1), (1, 0), (0, -1), (-1, 0)]);
+        let (r, c) = (row as isize + dr, col as isize + dc);
+        if r < 0 || c < 0 || r >= side as isize || c >= side as isize {
+            continue;
+        }
+        let (r, c) = (r as usize, c as usize);
+        if cells[r][c] {
+            continue;
+        }
+        cells[r][c] = true;
+        if pinched(&cells, r, c) || has_hole(&cells) {
+            cells[r][c] = false;
+            continue;
+        }
+        filled += 1;
+        frontier.push((r, c));
+    }
+    cells
+}
+
+/// Whether a 2x2 window around the cell has two cells that only touch by a corner
+fn pinched(cells: &[Vec<bool>], row: usize, col: usize) -> bool {
+    let side = cells.len();
+    let get = |r: usize, c: usize| r < side && c < side && cells[r][c];
+    for r in row.saturating_sub(1)..=row {
+        for c in col.saturating_sub(1)..=col {
+            let window = [get(r, c), get(r, c + 1), get(r + 1, c), get(r + 1, c + 1)];
+            if window == [true, false, false, true] || window == [false, true, true, false] {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Whether some empty cell cannot reach the border of the grid
+fn has_hole(cells: &[Vec<bool>]) -> bool {
+    let side = cells.len();
+    let mut seen = vec![vec![false; side + 2]; side + 2];
+    let empty = |r: usize, c: usize| {
+        r == 0 || c == 0 || r == side + 1 || c == side + 1 || !cells[r - 1][c - 1]
+    };
+    let mut stack = vec![(0usize, 0usize)];
+    seen[0][0] = true;
+    while let Some((r, c)) = stack.pop() {
+        let neighbours = [
+            (r.wrapping_sub(1), c),
+            (r + 1, c),
+            (r, c.wrapping_sub(1)),
+            (r, c + 1),
+        ];
+        for (nr, nc) in neighbours {
+            if nr <= side + 1 && nc <= side + 1 && !seen[nr][nc] && empty(nr, nc) {
+                seen[nr][nc] = true;
+                stack.push((nr, nc));
+            }
+        }
+    }
+    (0..side).any(|r| (0..side).any(|c| !cells[r][c] && !seen[r + 1][c + 1]))
+}
+
+/// Corners of the cells along the outline of a polyomino, in order, as `(row, col)`
+fn outline(cells: &[Vec<bool>]) -> Vec<(usize, usize)> {
+    let side = cells.len();
+    let filled = |r: isize, c: isize| {
+        r >= 0
+            && c >= 0
+            && (r as usize) < side
+            && (c as usize) < side
+            && cells[r as usize][c as usize]
+    };
+    // sides of the cells facing an empty cell, clockwise around each cell
+    let mut next = HashMap::new();
+    for r in 0..side as isize {
+        for c in 0..side as isize {
+            if !filled(r, c) {
+                continue;
+            }
+            let sides = [
+                ((-1, 0), (r, c), (r, c + 1)),
+                ((0, 1), (r, c + 1), (r + 1, c + 1)),
+                ((1, 0), (r + 1, c + 1), (r + 1, c)),
+                ((0, -1), (r + 1, c), (r, c)),
+            ];
+            for ((dr, dc), from, to) in sides {
+                if !filled(r + dr, c + dc) {
+                    next.insert(from, to);
+                }
+            }
+        }
+    }
+    let &start = next.keys().min().unwrap();
+    let mut res = vec![start];
+    let mut current = next[&start];
+    while current != start {
+        res.push(current);
+        current = next[&current];
+    }
+    res.into_iter()
+        .map(|(r, c)| (r as usize, c as usize))
+        .collect()
+}
+
+/// Day 10: a loop along the outline of a random shape in a `size + 1` square grid.
+/// By Pick's theorem, the tiles inside are the area of the shape minus half the loop, plus one.
+fn pipes(size: usize, rng: &mut Rng) -> Generated {
+    let cells = polyomino(size.max(2), rng);
+    let area = cells.iter().flatten().filter(|&&c| c).count();
+    let tiles = outline(&cells);
+    let side = cells.len() + 1;
+
+    let mut grid = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| *rng.choose(b"|-LJ7F..."))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    for i in 0..tiles.len() {
+        let (r, c) = tiles[i];
+        let neighbours = [
+            tiles[(i + tiles.len() - 1) % tiles.len()],
+            tiles[(i + 1) % tiles.len()],
+        ];
+        let connects = |dr: isize, dc: isize| {
+            neighbours
+                .iter()
+                .any(|&(nr, nc)| nr as isize - r as isize == dr && nc as isize - c as isize == dc)
+        };
+        grid[r][c] = match (
+            connects(-1, 0),
+            connects(1, 0),
+            connects(0, 1),
+            connects(0, -1),
+        ) {
+            (true, true, _, _) => b'|',
+            (_, _, true, true) => b'-',
+            (true, _, true, _) => b'L',
+            (true, _, _, true) => b'J',
+            (_, true, _, true) => b'7',
+            _ => b'F',
+        };
+    }
+    let on_loop = tiles.iter().copied().collect::<HashSet<_>>();
+    let (sr, sc) = *rng.choose(&tiles);
+    grid[sr][sc] = b'S';
+    // only the loop may connect to the start
+    for (r, c) in [
+        (sr.wrapping_sub(1), sc),
+        (sr + 1, sc),
+        (sr, sc.wrapping_sub(1)),
+        (sr, sc + 1),
+    ] {
+        if r < side && c < side && !on_loop.contains(&(r, c)) {
+            grid[r][c] = b'.';
+        }
+    }
+
+    let input = grid
+        .iter()
+        .map(|line| String::from_utf8_lossy(line).to_string() + "\n")
+        .collect();
+    let inside = area + 1 - tiles.len() / 2;
+    Generated::new(input, Some(tiles.len() as i128 / 2), Some(inside as i128))
+}
+
+/// Day 11: a `size` x `size` image, with some empty rows and columns
+fn galaxies(size: usize, rng: &mut Rng) -> Generated {
+    let side = size.max(2);
+    let cleared_rows = (0..side).map(|_| rng.chance(0.1)).collect::<Vec<_>>();
+    let cleared_cols = (0..side).map(|_| rng.chance(0.1)).collect::<Vec<_>>();
+    let mut galaxies = Vec::new();
+    let mut input = String::new();
+    for (row, &cleared_row) in cleared_rows.iter().enumerate() {
+        for (col, &cleared_col) in cleared_cols.iter().enumerate() {
+            let galaxy = !cleared_row && !cleared_col && rng.chance(0.03);
+            if galaxy {
+                galaxies.push((row, col));
+            }
+            input.push(if galaxy { '#' } else { '.' });
+        }
+        input.push('\n');
+    }
+    // other rows and columns may have been left empty by chance
+    let mut empty_rows = vec![true; side];
+    let mut empty_cols = vec![true; side];
+    for &(row, col) in galaxies.iter() {
+        empty_rows[row] = false;
+        empty_cols[col] = false;
+    }
+
+    // distances along one axis after expanding, summed over all pairs
+    let sum_distances = |coordinates: Vec<usize>, empty: &[bool], factor: usize| {
+        let mut expanded = Vec::new();
+        for x in coordinates {
+            let before = empty[..x].iter().filter(|&&e| e).count();
+            expanded.push((x + before * (factor - 1)) as i128);
+        }
+        expanded.sort();
+        let (mut res, mut prefix) = (0, 0);
+        for (i, &x) in expanded.iter().enumerate() {
+            res += x * i as i128 - prefix;
+            prefix += x;
+        }
+        res
+    };
+    let total = |factor: usize| {
+        let rows = galaxies.iter().map(|g| g.0).collect();
+        let cols = galaxies.iter().map(|g| g.1).collect();
+        sum_distances(rows, &empty_rows, factor) + sum_distances(cols, &empty_cols, factor)
+    };
+    Generated::new(input, Some(total(2)), Some(total(1_000_000)))
+}
+
+/// Day 12: `size` rows with some unknown springs
+fn springs(size: usize, rng: &mut Rng) -> Generated {
+    let mut input = String::new();
+    for _ in 0..size.max(1) {
+        let (row, groups) = loop {
+            let row = (0..rng.range(4..21))
+                .map(|_| rng.chance(0.5))
+                .collect::<Vec<_>>();
+            let runs = row.split(|&damaged| !damaged).map(|run| run.len());
+            let groups = runs.filter(|&len| len > 0).collect::<Vec<_>>();
+            if !groups.is_empty() {
+                break (row, groups);
+            }
+        };
+        for damaged in row {
+            input.push(match (rng.chance(0.4), damaged) {
+                (true, _) => '?',
+                (false, true) => '#',
+                (false, false) => '.',
+            });
+        }
+        let groups = groups.iter().map(|g| g.to_string());
+        writeln!(input, " {}", groups.collect::<Vec<_>>().join(",")).unwrap();
+    }
+    Generated::new(input, None, None)
+}
+
+/// Reflection lines of a pattern with exactly `smudges` differences, as their summary
+fn reflections(pattern: &[Vec<bool>], smudges: usize) -> Vec<usize> {
+    let (rows, cols) = (pattern.len(), pattern[0].len());
+    let mut res = Vec::new();
+    for col in 1..cols {
+        let width = col.min(cols - col);
+        let diff = (0..rows)
+            .flat_map(|r| (0..width).map(move |i| (r, i)))
+            .filter(|&(r, i)| pattern[r][col - 1 - i] != pattern[r][col + i])
+            .count();
+        if diff == smudges {
+            res.push(col);
+        }
+    }
+    for row in 1..rows {
+        let height = row.min(rows - row);
+        let diff = (0..height)
+            .flat_map(|i| (0..cols).map(move |c| (i, c)))
+            .filter(|&(i, c)| pattern[row - 1 - i][c] != pattern[row + i][c])
+            .count();
+        if diff == smudges {
+            res.push(100 * row);
+        }
+    }
+    res
+}
+
+/// Day 13: `size` patterns. Each one has a perfect vertical reflection near its left edge
+/// and a horizontal reflection broken by a single smudge outside of it (or the same,
+/// transposed).
+fn mirrors(size: usize, rng: &mut Rng) -> Generated {
+    let (mut input, mut part1, mut part2) = (String::new(), 0, 0);
+    for _ in 0..size.max(1) {
+        let pattern = loop {
+            let (rows, cols) = (rng.range(5..18), rng.range(5..18));
+            let line = rng.range(1..rows);
+            let mut pattern: Vec<Vec<bool>> = Vec::new();
+            for r in 0..rows {
+                let row = match r >= line && 2 * line > r {
+                    true => pattern[2 * line - 1 - r].clone(),
+                    false => {
+                        let mut row = (0..cols).map(|_| rng.chance(0.5)).collect::<Vec<_>>();
+                        // columns 0..4 mirror around the line between columns 1 and 2
+                        row[2] = row[1];
+                        row[3] = row[0];
+                        row
+                    }
+                };
+                pattern.push(row);
+            }
+            let overlap = line.min(rows - line);
+            let r = line - 1 - rng.range(0..overlap);
+            let c = rng.range(4..cols);
+            pattern[r][c] = !pattern[r][c];
+
+            let pattern = match rng.chance(0.5) {
+                true => (0..cols)
+                    .map(|c| (0..rows).map(|r| pattern[r][c]).collect())
+                    .collect(),
+                false => pattern,
+            };
+            if reflections(&pattern, 0).len() == 1 && reflections(&pattern, 1).len() == 1 {
+                break pattern;
+            }
+        };
+        part1 += reflections(&pattern, 0)[0];
+        part2 += reflections(&pattern, 1)[0];
+        for row in pattern.iter() {
+            let row = row.iter().map(|&rock| if rock { '#' } else { '.' });
+            writeln!(input, "{}", row.collect::<String>()).unwrap();
+        }
+        input.push('\n');
+    }
+    input.pop();
+    Generated::new(input, Some(part1 as i128), Some(part2 as i128))
+}
+
+/// Day 14: a `size` x `size` platform. The load after tilting north is easy to count.
+fn platform(size: usize, rng: &mut Rng) -> Generated {
+    let side = size.max(1);
+    let grid = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| match rng.range(0..10) {
+                    0..=1 => b'O',
+                    2 => b'#',
+                    _ => b'.',
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    // row where the next rounded rock of each column stops
+    let (mut load, mut free) = (0, vec![0; side]);
+    for (row, line) in grid.iter().enumerate() {
+        for (col, &tile) in line.iter().enumerate() {
+            match tile {
+                b'#' => free[col] = row + 1,
+                b'O' => {
+                    load += side - free[col];
+                    free[col] += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+    let input = grid
+        .iter()
+        .map(|line| String::from_utf8_lossy(line).to_string() + "\n")
+        .collect();
+    Generated::new(input, Some(load as i128), None)
+}
+
+/// Day 15: `size` steps over about `size / 3` labels
+fn init_sequence(size: usize, rng: &mut Rng) -> Generated {
+    let hash = |s: &str| s.bytes().fold(0, |h, b| (h + b as usize) * 17 % 256);
+    let mut used = HashSet::new();
+    let n_labels = size.div_ceil(3).max(1);
+    let min_len = name_len(n_labels, 2);
+    let labels = (0..n_labels)
+        .map(|_| {
+            let len = rng.range(min_len..min_len + 5);
+            unique_name(rng, len, &mut used)
+        })
+        .collect::<Vec<_>>();
+
+    let mut boxes: Vec<Vec<(String, usize)>> = vec![Vec::new(); 256];
+    let (mut steps, mut part1) = (Vec::new(), 0);
+    for _ in 0..size.max(1) {
+        let label = rng.choose(&labels).clone();
+        let lenses = &mut boxes[hash(&label)];
+        let position = lenses.iter().position(|(l, _)| *l == label);
+        let step = match rng.chance(0.3) {
+            true => {
+                if let Some(position) = position {
+                    lenses.remove(position);
+                }
+                format!("{}-", label)
+            }
+            false => {
+                let focal_length = rng.range(1..10);
+                match position {
+                    Some(position) => lenses[position].1 = focal_length,
+                    None => lenses.push((label.clone(), focal_length)),
+                }
+                format!("{}={}", label, focal_length)
+            }
+        };
+        part1 += hash(&step);
+        steps.push(step);
+    }
+    let part2 = boxes
+        .iter()
+        .enumerate()
+        .flat_map(|(i, lenses)| {
+            let slots = lenses.iter().enumerate();
+            slots.map(move |(j, (_, f))| (i + 1) * (j + 1) * f)
+        })
+        .sum::<usize>();
+    let input = steps.join(",") + "\n";
+    Generated::new(input, Some(part1 as i128), Some(part2 as i128))
+}
+
+/// Day 16: a `size` x `size` contraption
+fn contraption(size: usize, rng: &mut Rng) -> Generated {
+    let side = size.max(1);
+    let mut input = String::new();
+    for _ in 0..side {
+        for _ in 0..side {
+            input.push(match rng.range(0..40) {
+                0..=1 => '/',
+                2..=3 => '\\',
+                4..=5 => '|',
+                6..=7 => '-',
+                _ => '.',
+            });
+        }
+        input.push('\n');
+    }
+    Generated::new(input, None, None)
+}
+
+/// Day 17: a `size` x `size` map of heat losses
+fn heat_map(size: usize, rng: &mut Rng) -> Generated {
+    let side = size.max(2);
+    let mut input = String::new();
+    for _ in 0..side {
+        for _ in 0..side {
+            write!(input, "{}", rng.range(1..10)).unwrap();
+        }
+        input.push('\n');
+    }
+    Generated::new(input, None, None)
+}
+
+/// Vertices of the outline of a polyomino, with the columns and rows stretched by random
+/// gaps of up to `max_gap`. The stretching keeps the polygon simple.
+fn stretched(
+    corners: &[(usize, usize)],
+    side: usize,
+    max_gap: usize,
+    rng: &mut Rng,
+) -> Vec<(i128, i128)> {
+    let positions = |rng: &mut Rng| {
+        let mut res = vec![0i128];
+        for _ in 0..side {
+            res.push(res[res.len() - 1] + rng.range(1..max_gap + 1) as i128);
+        }
+        res
+    };
+    let (rows, cols) = (positions(rng), positions(rng));
+    corners.iter().map(|&(r, c)| (rows[r], cols[c])).collect()
+}
+
+/// Lagoon volume of a dig plan that follows a simple polygon: area by the shoelace
+/// formula, plus the half of the trench outside it
+fn lagoon(vertices: &[(i128, i128)]) -> i128 {
+    let n = vertices.len();
+    let (mut area, mut perimeter) = (0, 0);
+    for i in 0..n {
+        let ((r1, c1), (r2, c2)) = (vertices[i], vertices[(i + 1) % n]);
+        area += c1 * r2 - c2 * r1;
+        perimeter += (r2 - r1).abs() + (c2 - c1).abs();
+    }
+    area.abs() / 2 + perimeter / 2 + 1
+}
+
+/// Day 18: the outline of a random shape in a `size` x `size` grid. Both parts follow the
+/// same turns with different lengths, so both plans are simple polygons.
+fn dig_plan(size: usize, rng: &mut Rng) -> Generated {
+    let side = size.max(2);
+    let tiles = outline(&polyomino(side, rng));
+    // keep the corners only
+    let n = tiles.len();
+    let corners = (0..n)
+        .filter(|&i| {
+            let (prev, next) = (tiles[(i + n - 1) % n], tiles[(i + 1) % n]);
+            prev.0 != next.0 && prev.1 != next.1
+        })
+        .map(|i| tiles[i])
+        .collect::<Vec<_>>();
+    let small = stretched(&corners, side, 10, rng);
+    let large = stretched(&corners, side, (0xfffff / side).max(1), rng);
+
+    let mut input = String::new();
+    for i in 0..small.len() {
+        let step = |vertices: &[(i128, i128)]| {
+            let ((r1, c1), (r2, c2)) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+            match (r2 - r1, c2 - c1) {
+                (0, d) if d > 0 => ('R', 0, d),
+                (d, 0) if d > 0 => ('D', 1, d),
+                (0, d) => ('L', 2, -d),
+                (d, _) => ('U', 3, -d),
+            }
+        };
+        let ((dir, _, len), (_, code, color)) = (step(&small), step(&large));
+        writeln!(input, "{} {} (#{:05x}{})", dir, len, color, code).unwrap();
+    }
+    Generated::new(input, Some(lagoon(&small)), Some(lagoon(&large)))
+}
+
+/// Rule of a generated workflow: `(attribute, is less than, value)` and target
+type GeneratedRule = (Option<(usize, bool, usize)>, GeneratedTarget);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GeneratedTarget {
+    Accept,
+    Reject,
+    Workflow(usize),
+}
+
+/// Day 19: about `size` workflows and `size` parts. Workflows form a tree, so there are
+/// no cycles.
+fn workflows(size: usize, rng: &mut Rng) -> Generated {
+    let attributes = ["x", "m", "a", "s"];
+    let mut used = HashSet::from(["in".to_string()]);
+    let mut names = vec!["in".to_string()];
+    let mut rules: Vec<Vec<GeneratedRule>> = vec![Vec::new()];
+    let mut pending = vec![0];
+    let min_len = name_len(size, 2);
+    while let Some(workflow) = pending.pop() {
+        let mut target = |rng: &mut Rng| match names.len() < size.max(1) && rng.chance(0.6) {
+            true => {
+                let len = rng.range(min_len..min_len + 2);
+                names.push(unique_name(rng, len, &mut used));
+                rules.push(Vec::new());
+                pending.push(names.len() - 1);
+                GeneratedTarget::Workflow(names.len() - 1)
+            }
+            false if rng.chance(0.5) => GeneratedTarget::Accept,
+            false => GeneratedTarget::Reject,
+        };
+        let mut workflow_rules = Vec::new();
+        for _ in 0..rng.range(1..5) {
+            let condition = (rng.range(0..4), rng.chance(0.5), rng.range(1..4001));
+            workflow_rules.push((Some(condition), target(rng)));
+        }
+        workflow_rules.push((None, target(rng)));
+        rules[workflow] = workflow_rules;
+    }
+
+    let mut order = (0..names.len()).collect::<Vec<_>>();
+    rng.shuffle(&mut order);
+    let mut input = String::new();
+    let target_name = |target: GeneratedTarget| match target {
+        GeneratedTarget::Accept => "A",
+        GeneratedTarget::Reject => "R",
+        GeneratedTarget::Workflow(id) => names[id].as_str(),
+    };
+    for &id in order.iter() {
+        let formatted = rules[id]
+            .iter()
+            .map(|&(condition, target)| match condition {
+                Some((attr, less, value)) => format!(
+                    "{}{}{}:{}",
+                    attributes[attr],
+                    if less { '<' } else { '>' },
+                    value,
+                    target_name(target)
+                ),
+                None => target_name(target).to_string(),
+            });
+        writeln!(
+            input,
+            "{}{{{}}}",
+            names[id],
+            formatted.collect::<Vec<_>>().join(",")
+        )
+        .unwrap();
+    }
+    input.push('\n');
+
+    let matches = |condition: Option<(usize, bool, usize)>, part: &[usize; 4]| match condition {
+        Some((attr, true, value)) => part[attr] < value,
+        Some((attr, false, value)) => part[attr] > value,
+        None => true,
+    };
+    let mut part1 = 0;
+    for _ in 0..size.max(1) {
+        let part = [0; 4].map(|_| rng.range(1..4001));
+        writeln!(
+            input,
+            "{{x={},m={},a={},s={}}}",
+            part[0], part[1], part[2], part[3]
+        )
+        .unwrap();
+        let mut workflow = 0;
+        loop {
+            let rule = rules[workflow].iter().find(|(c, _)| matches(*c, &part));
+            match rule.unwrap().1 {
+                GeneratedTarget::Accept => part1 += part.iter().sum::<usize>(),
+                GeneratedTarget::Reject => {}
+                GeneratedTarget::Workflow(next) => {
+                    workflow = next;
+                    continue;
+                }
+            }
+            break;
+        }
+    }
+
+    // accepted combinations, splitting inclusive ranges at every condition
+    let mut part2 = 0u128;
+    let mut stack = vec![(0, 0, [(1usize, 4000usize); 4])];
+    while let Some((workflow, index, ranges)) = stack.pop() {
+        let (condition, target) = rules[workflow][index];
+        let (mut pass, mut fail) = (Some(ranges), None);
+        if let Some((attr, less, value)) = condition {
+            let (low, high) = ranges[attr];
+            let (pass_range, fail_range) = match less {
+                true => ((low, high.min(value - 1)), (low.max(value), high)),
+                false => ((low.max(value + 1), high), (low, high.min(value))),
+            };
+            let with = |range: (usize, usize)| {
+                let mut res = ranges;
+                res[attr] = range;
+                (range.0 <= range.1).then_some(res)
+            };
+            (pass, fail) = (with(pass_range), with(fail_range));
+        }
+        if let Some(ranges) = pass {
+            match target {
+                GeneratedTarget::Accept => {
+                    let sizes = ranges.iter().map(|(l, h)| (h - l + 1) as u128);
+                    part2 += sizes.product::<u128>();
+                }
+                GeneratedTarget::Reject => {}
+                GeneratedTarget::Workflow(next) => stack.push((next, 0, ranges)),
+            }
+        }
+        if let Some(ranges) = fail {
+            stack.push((workflow, index + 1, ranges));
+        }
+    }
+    Generated::new(input, Some(part1 as i128), Some(part2 as i128))
+}
+
+/// Day 20: `size` binary counters of 12 flip-flops, each wired to a conjunction that
+/// fires once per period, then to an inverter, and all of them to the conjunction that
+/// feeds `rx`. The periods are odd and have their top bit set.
+fn modules(size: usize, rng: &mut Rng) -> Generated {
+    let mut used = HashSet::from(["rx".to_string()]);
+    let mut lines = Vec::new();
+    let mut heads = Vec::new();
+    let mut inverters = Vec::new();
+    let mut periods = Vec::new();
+    let last = unique_name(rng, 2, &mut used);
+    for _ in 0..size.clamp(1, 8) {
+        let period = rng.range(0..1 << 12) | 1 | 1 << 11;
+        periods.push(period);
+        let flip_flops = (0..12)
+            .map(|_| unique_name(rng, 2, &mut used))
+            .collect::<Vec<_>>();
+        let (conjunction, inverter) = (
+            unique_name(rng, 2, &mut used),
+            unique_name(rng, 2, &mut used),
+        );
+        // the conjunction resets the bits that are not set in the period, and the first one
+        let mut resets = vec![flip_flops[0].clone()];
+        for (bit, name) in flip_flops.iter().enumerate() {
+            let mut outputs = Vec::new();
+            if bit + 1 < 12 {
+                outputs.push(flip_flops[bit + 1].clone());
+            }
+            match period >> bit & 1 {
+                1 => outputs.push(conjunction.clone()),
+                _ => resets.push(name.clone()),
+            }
+            rng.shuffle(&mut outputs);
+            lines.push(format!("%{} -> {}", name, outputs.join(", ")));
+        }
+        resets.push(inverter.clone());
+        rng.shuffle(&mut resets);
+        lines.push(format!("&{} -> {}", conjunction, resets.join(", ")));
+        lines.push(format!("&{} -> {}", inverter, last));
+        heads.push(flip_flops[0].clone());
+        inverters.push(inverter);
+    }
+    lines.push(format!("&{} -> rx", last));
+    lines.push(format!("broadcaster -> {}", heads.join(", ")));
+    rng.shuffle(&mut lines);
+
+    // up to eight 12-bit periods do not fit in a `usize`, but always fit in an `i128`
+    let part2 = periods.iter().try_fold(1, |acc, &p| lcm(acc, p as u128));
+    let part2 = part2.and_then(|n| i128::try_from(n).ok());
+    Generated::new(lines.join("\n") + "\n", None, part2)
+}
+
+/// Day 21: an odd `size` x `size` garden, starting in the middle, with clear middle
+/// row and column, borders and diamond as in the puzzle inputs
+fn garden(size: usize, rng: &mut Rng) -> Generated {
+    let side = size.max(5) | 1;
+    let middle = side / 2;
+    let mut input = String::new();
+    for row in 0..side {
+        for col in 0..side {
+            let diamond = row.abs_diff(middle) + col.abs_diff(middle);
+            let clear = row == middle
+                || col == middle
+                || row == 0
+                || col == 0
+                || row == side - 1
+                || col == side - 1
+                || diamond.abs_diff(middle) <= 1;
+            input.push(match (row, col) {
+                _ if row == middle && col == middle => 'S',
+                _ if !clear && rng.chance(0.12) => '#',
+                _ => '.',
+            });
+        }
+        input.push('\n');
+    }
+    Generated::new(input, None, None)
+}
+
+/// Day 22: `size` bricks falling on a 10 x 10 area
+fn bricks(size: usize, rng: &mut Rng) -> Generated {
+    let mut bricks = Vec::new();
+    let mut z = 1;
+    for _ in 0..size.max(1) {
+        let start = [rng.range(0..10), rng.range(0..10), z];
+        let mut end = start;
+        let axis = rng.range(0..3);
+        end[axis] = (end[axis] + rng.range(0..4)).min(if axis == 2 { usize::MAX } else { 9 });
+        z = end[2] + rng.range(1..3);
+        bricks.push(format!(
+            "{},{},{}~{},{},{}",
+            start[0], start[1], start[2], end[0], end[1], end[2]
+        ));
+    }
+    rng.shuffle(&mut bricks);
+    Generated::new(bricks.join("\n") + "\n", None, None)
+}
+
+/// Day 23: a lattice of `size` x `size` junctions at random spacings, joined by straight
+/// trails with slopes pointing down and right around every junction. `size` is clamped to
+/// 6: the longest hike of part 2 takes a fraction of a second at 6 x 6 but over a minute
+/// at 7 x 7.
+fn trails(size: usize, rng: &mut Rng) -> Generated {
+    let junctions = size.clamp(2, 6);
+    // rows and columns of the junctions, with room for a slope at both ends of each trail
+    let positions = |rng: &mut Rng| {
+        let mut res = vec![1 + rng.range(1..8)];
+        for _ in 1..junctions {
+            res.push(res[res.len() - 1] + rng.range(4..25));
+        }
+        res
+    };
+    let (rows, cols) = (positions(rng), positions(rng));
+    let (height, width) = (rows[junctions - 1] + 3, cols[junctions - 1] + 3);
+    let mut grid = vec![vec![b'#'; width]; height];
+    for &row in rows.iter() {
+        grid[row][cols[0]..=cols[junctions - 1]].fill(b'.');
+        for &col in cols.iter() {
+            if col > cols[0] {
+                grid[row][col - 1] = b'>';
+            }
+            if col < cols[junctions - 1] {
+                grid[row][col + 1] = b'>';
+            }
+        }
+    }
+    for &col in cols.iter() {
+        for line in grid[rows[0]..=rows[junctions - 1]].iter_mut() {
+            line[col] = b'.';
+        }
+        for &row in rows.iter() {
+            if row > rows[0] {
+                grid[row - 1][col] = b'v';
+            }
+            if row < rows[junctions - 1] {
+                grid[row + 1][col] = b'v';
+            }
+        }
+    }
+    // the start above the first junction, the end below the last one
+    for line in grid[..rows[0]].iter_mut() {
+        line[cols[0]] = b'.';
+    }
+    for line in grid[rows[junctions - 1] + 1..].iter_mut() {
+        line[cols[junctions - 1]] = b'.';
+    }
+
+    let input = grid
+        .iter()
+        .map(|line| String::from_utf8_lossy(line).to_string() + "\n")
+        .collect();
+    // going down or right only, every hike has the length of the Manhattan distance
+    let part1 = height - 1 + cols[junctions - 1] - cols[0];
+    Generated::new(input, Some(part1 as i128), None)
+}
+
+/// Day 24: `size` hailstones (at least 4, as the solver needs), all on the path of a rock
+/// thrown from a random position
+fn hailstones(size: usize, rng: &mut Rng) -> Generated {
+    let span = |rng: &mut Rng, low: usize, high: usize| rng.range(low..high) as i128;
+    let rock_position = [0; 3].map(|_| span(rng, 100_000_000_000_000, 400_000_000_000_000));
+    let rock_velocity = [0; 3].map(|_| span(rng, 0, 600) - 300);
+    let mut times = HashSet::new();
+    let mut input = String::new();
+    while times.len() < size.max(4) {
+        let time = span(rng, 100_000_000_000, 1_000_000_000_000);
+        let velocity = [0; 3].map(|_| span(rng, 0, 1000) - 500);
+        if velocity == rock_velocity || !times.insert(time) {
+            continue;
+        }
+        let position =
+            [0, 1, 2].map(|k| rock_position[k] + time * (rock_velocity[k] - velocity[k]));
+        writeln!(
+            input,
+            "{}, {}, {} @ {}, {}, {}",
+            position[0], position[1], position[2], velocity[0], velocity[1], velocity[2]
+        )
+        .unwrap();
+    }
+    Generated::new(input, None, Some(rock_position.iter().sum()))
+}
+
+/// Day 25: `size` components in two groups joined by three wires. Each group links every
+/// component to the next two, so cutting it takes at least four wires.
+fn wiring(size: usize, rng: &mut Rng) -> Generated {
+    let total = size.max(10);
+    let first = rng.range(5..total - 4);
+    let mut used = HashSet::new();
+    let len = name_len(total, 3);
+    let mut names = (0..total)
+        .map(|_| unique_name(rng, len, &mut used))
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut names);
+
+    let mut edges = HashSet::new();
+    for (start, len) in [(0, first), (first, total - first)] {
+        for i in 0..len {
+            for step in [1, 2] {
+                let (a, b) = (start + i, start + (i + step) % len);
+                edges.insert((a.min(b), a.max(b)));
+            }
+            if rng.chance(0.3) {
+                let (a, b) = (start + i, start + rng.range(0..len));
+                if a != b {
+                    edges.insert((a.min(b), a.max(b)));
+                }
+            }
+        }
+    }
+    let mut bridged = HashSet::new();
+    while bridged.len() < 3 {
+        bridged.insert((rng.range(0..first), rng.range(first..total)));
+    }
+    edges.extend(bridged);
+
+    // sorted, so that the same seed picks the same side of each wire
+    let mut edges = edges.into_iter().collect::<Vec<_>>();
+    edges.sort();
+    let mut adjacency: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (a, b) in edges {
+        match rng.chance(0.5) {
+            true => adjacency.entry(a).or_default().push(b),
+            false => adjacency.entry(b).or_default().push(a),
+        }
+    }
+    let mut lines = adjacency
+        .iter()
+        .map(|(&a, bs)| {
+            let bs = bs.iter().map(|&b| names[b].as_str());
+            format!("{}: {}", names[a], bs.collect::<Vec<_>>().join(" "))
+        })
+        .collect::<Vec<_>>();
+    lines.sort();
+    rng.shuffle(&mut lines);
+    let part1 = first * (total - first);
+    Generated::new(lines.join("\n") + "\n", Some(part1 as i128), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// More names than the lengths these days start from can hold
+    #[test]
+    fn names_do_not_run_out() {
+        for (day, size) in [(15, 30_000), (19, 1500), (25, 26 * 26 * 26 + 1)] {
+            let generated = generate(day, size, &mut Rng::new(0));
+            assert!(
+                generated.is_some_and(|g| !g.input.is_empty()),
+                "day {}",
+                day
+            );
+        }
+    }
+}
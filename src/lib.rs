@@ -1,5 +1,14 @@
+pub mod batch;
+pub mod check;
+pub mod decision;
+pub mod generate;
+pub mod rng;
+pub mod search;
+pub mod springs;
+pub mod workflow;
+
 /// Greatest common divisor
-pub fn gcd(mut a: usize, mut b: usize) -> usize {
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
     while b != 0 {
         let tmp = b;
         b = a % b;
@@ -8,9 +17,12 @@ pub fn gcd(mut a: usize, mut b: usize) -> usize {
     a
 }
 
-/// Least common multiple
-pub fn lcm(a: usize, b: usize) -> usize {
-    a * b / gcd(a, b)
+/// Least common multiple, or `None` if it does not fit in a `u128`
+pub fn lcm(a: u128, b: u128) -> Option<u128> {
+    match gcd(a, b) {
+        0 => Some(0),
+        g => (a / g).checked_mul(b),
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
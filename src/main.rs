@@ -1,295 +1,142 @@
-use core::panic;
-use std::collections::HashMap;
+use aoc_2023::batch::Program;
+use aoc_2023::decision::DecisionTree;
+use aoc_2023::workflow::{self, Part, PartRange, Target, WorkflowId, Workflows};
 use std::fs;
-use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Part {
-    x: usize,
-    m: usize,
-    a: usize,
-    s: usize,
-}
-
-impl Part {
-    fn apply_rule<'a>(&self, rule: &'a str) -> Option<&'a str> {
-        let rule = rule.split(':').collect::<Vec<_>>();
-        match rule.len() {
-            1 => Some(rule[0]),
-            2 => {
-                let mut condition = rule[0].trim().chars();
-                let attr = condition.next().unwrap();
-                let cond = condition.next().unwrap();
-                let val: usize = condition.collect::<String>().parse().unwrap();
-                let num = match attr {
-                    'x' => self.x,
-                    'm' => self.m,
-                    'a' => self.a,
-                    's' => self.s,
-                    _ => panic!("invalid attribute"),
-                };
-                match cond {
-                    '<' => {
-                        if num < val {
-                            Some(rule[1])
-                        } else {
-                            None
-                        }
-                    }
-                    '>' => {
-                        if num > val {
-                            Some(rule[1])
-                        } else {
-                            None
-                        }
-                    }
-                    _ => panic!("invalid condition"),
-                }
-            }
-            _ => panic!("invalid rule"),
+fn parse_input(path: &str) -> (Workflows, Vec<Part>) {
+    let file = fs::read_to_string(path).expect("cannot read file");
+    workflow::parse(&file).unwrap_or_else(|diagnostics| {
+        for diagnostic in diagnostics {
+            eprintln!("{}:{}", path, diagnostic);
         }
-    }
+        std::process::exit(1);
+    })
 }
 
-impl FromStr for Part {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s
-            .trim_start_matches('{')
-            .trim_end_matches('}')
-            .split(',')
-            .map(|s| s.split('=').last().unwrap())
-            .collect::<Vec<_>>();
-        let x = s[0].parse().unwrap();
-        let m = s[1].parse().unwrap();
-        let a = s[2].parse().unwrap();
-        let s = s[3].parse().unwrap();
-        Ok(Self { x, m, a, s })
-    }
+fn exercise_1(workflows: &Workflows, parts: &[Part]) -> u64 {
+    Program::compile(workflows).evaluate(parts).accepted_rating
 }
 
-impl std::fmt::Display for Part {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{{x={}, m={}, a={}, s={}}}",
-            self.x, self.m, self.a, self.s
-        )
+/// Scores a file of parts, one per line, and shows the busiest workflows
+fn batch(workflows: &Workflows, path: &str, threads: usize) {
+    let file = fs::File::open(path).expect("cannot read file");
+    let program = Program::compile(workflows).threads(threads);
+    let result = program
+        .evaluate_reader(std::io::BufReader::new(file))
+        .unwrap_or_else(|error| {
+            eprintln!("{}: {}", path, error);
+            std::process::exit(1);
+        });
+    println!(
+        "{} parts, {} accepted, total rating {}",
+        result.parts, result.accepted, result.accepted_rating
+    );
+    let mut hits = result.hits.iter().enumerate().collect::<Vec<_>>();
+    hits.sort_by_key(|&(_, &hits)| std::cmp::Reverse(hits));
+    for (id, hits) in hits.into_iter().take(10) {
+        println!("  {}: {}", workflows[WorkflowId(id)].name, hits);
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Instruction {
-    name: String,
-    rules: Vec<String>,
-}
-
-impl std::fmt::Display for Instruction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {:?}", self.name, self.rules)
-    }
-}
-
-impl FromStr for Instruction {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.trim_end_matches('}').split('{').collect::<Vec<_>>();
-        let name = s[0].to_string();
-        let rules = s[1].split(',').map(|s| s.to_string()).collect::<Vec<_>>();
-        Ok(Self { name, rules })
-    }
-}
-
-fn parse_input(path: &str) -> (HashMap<String, Instruction>, Vec<Part>) {
-    let (mut instructions, mut parts) = (HashMap::new(), vec![]);
-    let file = fs::read_to_string(path).expect("cannot read file");
-    let mut lines = file.lines().into_iter();
-
-    while let Some(line) = lines.next() {
-        if line.is_empty() {
-            break;
-        }
-        let instruction: Instruction = line.parse().unwrap();
-        instructions.insert(instruction.name.clone(), instruction);
-    }
-
-    while let Some(line) = lines.next() {
-        let part = line.parse().unwrap();
-        parts.push(part);
-    }
-
-    (instructions, parts)
-}
-
-fn exercise_1(instructions: &HashMap<String, Instruction>, parts: &[Part]) -> usize {
+fn exercise_2(workflows: &Workflows) -> u128 {
     let (mut accepted, mut rejected) = (vec![], vec![]);
 
-    for part in parts {
-        print!("- {}: in", part);
-        let mut done = false;
-        let mut instruction = instructions.get("in").unwrap();
-        loop {
-            for rule in &instruction.rules {
-                if let Some(next) = part.apply_rule(rule) {
-                    print!(" -> {}", next);
-                    match next {
-                        "A" => {
-                            done = true;
-                            accepted.push(part);
-                        }
-                        "R" => {
-                            done = true;
-                            rejected.push(part);
-                        }
-                        _ => {
-                            if let Some(next_instruction) = instructions.get(next) {
-                                instruction = next_instruction;
-                            }
-                        }
-                    }
-                    break;
+    // ranges still to be classified, with the workflow and the index of the rule to apply
+    let mut new_parts: Vec<(PartRange, WorkflowId, usize)> =
+        vec![(PartRange::new(&workflows.schema), workflows.start, 0)];
+
+    while let Some((part, workflow, rule_index)) = new_parts.pop() {
+        let rule = &workflows[workflow].rules[rule_index];
+        let (acc, rej) = part.split(&rule.condition);
+        for a in acc {
+            match rule.target {
+                Target::Accept => {
+                    accepted.push(a);
+                }
+                Target::Reject => {
+                    rejected.push(a);
+                }
+                Target::Workflow(next) => {
+                    new_parts.push((a, next, 0));
                 }
-            }
-            if done {
-                break;
             }
         }
-        println!();
+        for pending in rej {
+            new_parts.push((pending, workflow, rule_index + 1));
+        }
     }
-    accepted.iter().map(|p| p.x + p.m + p.a + p.s).sum()
+    accepted.iter().map(|p| p.len()).sum()
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct PartRange {
-    x: (usize, usize),
-    m: (usize, usize),
-    a: (usize, usize),
-    s: (usize, usize),
+/// Flags that write the workflow graph, the decision tree and the leaf list to a file
+const OUTPUTS: [&str; 3] = ["--graph", "--tree", "--leaves"];
+
+fn usage(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("usage: aoc-2023 [--graph <dot file>] [--tree <dot file>] [--leaves <file>]");
+    eprintln!("       aoc-2023 --batch <parts file> [threads]");
+    std::process::exit(1);
 }
 
-impl PartRange {
-    fn new() -> Self {
-        Self {
-            x: (1, 4_000),
-            m: (1, 4_000),
-            a: (1, 4_000),
-            s: (1, 4_000),
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    // `--batch <parts file> [threads]` only scores the given parts
+    if args.first().is_some_and(|arg| arg == "--batch") {
+        let path = args.get(1).unwrap_or_else(|| usage("missing parts file"));
+        let threads = match args.get(2) {
+            Some(threads) => threads.parse().expect("invalid number of threads"),
+            None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        };
+        if let Some(arg) = args.get(3) {
+            usage(&format!("unknown argument '{}'", arg));
         }
+        let (workflows, _) = parse_input("data/19_input.txt");
+        batch(&workflows, path, threads);
+        return;
     }
 
-    fn len(&self) -> usize {
-        (self.x.1 - self.x.0 + 1)
-            * (self.m.1 - self.m.0 + 1)
-            * (self.a.1 - self.a.0 + 1)
-            * (self.s.1 - self.s.0 + 1)
-    }
-
-    fn get_range(&self, attr: char) -> (usize, usize) {
-        match attr {
-            'x' => self.x,
-            'm' => self.m,
-            'a' => self.a,
-            's' => self.s,
-            _ => panic!("invalid attribute"),
-        }
+    let mut outputs: [Option<&String>; 3] = [None; 3];
+    let mut flags = args.iter();
+    while let Some(arg) = flags.next() {
+        let Some(i) = OUTPUTS.iter().position(|flag| flag == arg) else {
+            usage(&format!("unknown argument '{}'", arg));
+        };
+        outputs[i] = Some(flags.next().unwrap_or_else(|| usage("missing output file")));
     }
-
-    fn set_range(&mut self, attr: char, range: (usize, usize)) {
-        match attr {
-            'x' => self.x = range,
-            'm' => self.m = range,
-            'a' => self.a = range,
-            's' => self.s = range,
-            _ => panic!("invalid attribute"),
-        }
+    let (workflows, parts) = parse_input("data/19_input.txt");
+
+    println!("exercise 1: {}", exercise_1(&workflows, &parts));
+    println!("exercise 2: {}", exercise_2(&workflows));
+
+    let tree = DecisionTree::compile(&workflows);
+    let leaves = tree.leaves();
+    let accepted = leaves.iter().filter(|leaf| leaf.accepted).count();
+    println!(
+        "decision tree: {} accepted and {} rejected ranges, {} accepted parts",
+        accepted,
+        leaves.len() - accepted,
+        tree.accepted_volume()
+    );
+
+    let minimised = workflows.minimise();
+    println!(
+        "minimised: {} workflows with {} rules (from {} with {}), exercise 2: {}",
+        minimised.workflows.len(),
+        minimised.rule_count(),
+        workflows.workflows.len(),
+        workflows.rule_count(),
+        exercise_2(&minimised)
+    );
+
+    let [graph, tree_dot, leaves] = outputs;
+    if let Some(path) = graph {
+        fs::write(path, workflows.to_dot()).expect("cannot write file");
     }
-
-    fn apply_rule(self, rule: &str) -> (&str, Option<Self>, Option<Self>) {
-        let rule = rule.split(':').collect::<Vec<_>>();
-        match rule.len() {
-            1 => (rule[0], Some(self), None),
-            2 => {
-                let mut condition = rule[0].trim().chars();
-                let attr = condition.next().unwrap();
-                let cond = condition.next().unwrap();
-                let val: usize = condition.collect::<String>().parse().unwrap();
-                let range = self.get_range(attr);
-
-                let next = rule[1];
-                let (mut accepted, mut rejected) = (None, None);
-
-                match cond {
-                    '<' => {
-                        if range.1 < val {
-                            accepted = Some(self);
-                        } else if range.0 > val {
-                            rejected = Some(self);
-                        } else {
-                            let (mut acc, mut rej) = (self, self);
-                            acc.set_range(attr, (range.0, val - 1));
-                            rej.set_range(attr, (val, range.1));
-                            (accepted, rejected) = (Some(acc), Some(rej));
-                        }
-                    }
-                    '>' => {
-                        if range.0 > val {
-                            accepted = Some(self);
-                        } else if range.1 < val {
-                            rejected = Some(self);
-                        } else {
-                            let (mut acc, mut rej) = (self, self);
-                            acc.set_range(attr, (val + 1, range.1));
-                            rej.set_range(attr, (range.0, val));
-                            (accepted, rejected) = (Some(acc), Some(rej));
-                        }
-                    }
-                    _ => panic!("invalid condition"),
-                };
-                (next, accepted, rejected)
-            }
-            _ => panic!("invalid rule"),
-        }
+    if let Some(path) = tree_dot {
+        fs::write(path, tree.to_dot(&workflows)).expect("cannot write file");
     }
-}
-
-fn exercise_2(instructions: &HashMap<String, Instruction>) -> usize {
-    let (mut accepted, mut rejected) = (vec![], vec![]);
-
-    let mut new_parts = vec![(PartRange::new(), "in")];
-
-    while let Some((mut part, instruction)) = new_parts.pop() {
-        let instruction = instructions.get(instruction).unwrap();
-        for rule in &instruction.rules {
-            let (next, acc, rej) = part.apply_rule(rule);
-            if let Some(a) = acc {
-                match next {
-                    "A" => {
-                        accepted.push(a);
-                    }
-                    "R" => {
-                        rejected.push(a);
-                    }
-                    _ => {
-                        new_parts.push((a, next));
-                    }
-                }
-            }
-            if let Some(pending) = rej {
-                part = pending;
-            } else {
-                break;
-            }
-        }
+    if let Some(path) = leaves {
+        fs::write(path, tree.format_leaves(&workflows)).expect("cannot write file");
     }
-    accepted.iter().map(|p| p.len()).sum()
-}
-
-fn main() {
-    let (instructions, parts) = parse_input("data/19_input.txt");
-
-    println!("exercise 1: {}", exercise_1(&instructions, &parts));
-    println!("exercise 2: {}", exercise_2(&instructions));
-    // exercise_1(&instructions);
 }
@@ -0,0 +1,318 @@
+//! Shortest paths on weighted grids where movement is constrained by the previous moves
+//! (e.g., the crucibles of day 17).
+
+use crate::Point;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::North, Dir::East, Dir::South, Dir::West];
+
+    pub fn delta(&self) -> Point {
+        match self {
+            Dir::North => Point::new(-1, 0),
+            Dir::East => Point::new(0, 1),
+            Dir::South => Point::new(1, 0),
+            Dir::West => Point::new(0, -1),
+        }
+    }
+
+    pub fn reverse(&self) -> Self {
+        Self::ALL[(*self as usize + 2) % 4]
+    }
+
+    pub fn left(&self) -> Self {
+        Self::ALL[(*self as usize + 3) % 4]
+    }
+
+    pub fn right(&self) -> Self {
+        Self::ALL[(*self as usize + 1) % 4]
+    }
+}
+
+/// Kind of move from one tile to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Straight,
+    Turn,
+    Reverse,
+}
+
+/// Movement model of the searcher.
+pub trait MovementRule {
+    /// Tiles to move in a straight line before turning or stopping
+    fn min_run(&self) -> usize {
+        1
+    }
+
+    /// Maximum tiles to move in a straight line
+    fn max_run(&self) -> usize;
+
+    /// Whether the searcher can go back the way it came
+    fn allow_reverse(&self) -> bool {
+        false
+    }
+
+    /// Cost of entering a tile of the given weight. `None` means the tile cannot be entered.
+    fn cost(&self, weight: u32, _movement: Move) -> Option<u32> {
+        Some(weight)
+    }
+}
+
+/// Priority queue used by the search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Queue {
+    /// Binary heap, for any costs
+    Heap,
+    /// Bucket queue (Dial's algorithm), fast when costs are small integers
+    Buckets,
+}
+
+/// A searcher at `point` that entered it moving towards `dir`, after `run` straight tiles.
+/// Starting states have `run == 0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    pub point: Point,
+    pub dir: Dir,
+    pub run: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    pub cost: u32,
+    /// States from a start to a goal, both included
+    pub states: Vec<State>,
+}
+
+pub struct Search<'a, R> {
+    weights: &'a [Vec<u32>],
+    rule: R,
+    queue: Queue,
+    heuristic: bool,
+}
+
+enum Frontier {
+    Heap(BinaryHeap<Reverse<(u32, usize)>>),
+    Buckets {
+        buckets: Vec<Vec<usize>>,
+        current: usize,
+    },
+}
+
+impl Frontier {
+    fn push(&mut self, priority: u32, state: usize) {
+        match self {
+            Self::Heap(heap) => heap.push(Reverse((priority, state))),
+            Self::Buckets { buckets, .. } => {
+                let priority = priority as usize;
+                if buckets.len() <= priority {
+                    buckets.resize(priority + 1, Vec::new());
+                }
+                buckets[priority].push(state);
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<(u32, usize)> {
+        match self {
+            Self::Heap(heap) => heap.pop().map(|Reverse(item)| item),
+            Self::Buckets { buckets, current } => {
+                while *current < buckets.len() {
+                    if let Some(state) = buckets[*current].pop() {
+                        return Some((*current as u32, state));
+                    }
+                    *current += 1;
+                }
+                None
+            }
+        }
+    }
+}
+
+impl<'a, R: MovementRule> Search<'a, R> {
+    /// Panics if `max_run` is 0, as runs would not fit in the dense state tables, or if
+    /// `min_run` is above `max_run`, as the searcher could then never turn.
+    pub fn new(weights: &'a [Vec<u32>], rule: R) -> Self {
+        assert!(rule.max_run() >= 1, "max_run must be at least 1");
+        assert!(
+            rule.min_run() <= rule.max_run(),
+            "min_run must not exceed max_run"
+        );
+        Self {
+            weights,
+            rule,
+            queue: Queue::Heap,
+            heuristic: true,
+        }
+    }
+
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = queue;
+        self
+    }
+
+    /// Enables or disables the A* heuristic (plain Dijkstra when disabled)
+    pub fn heuristic(mut self, heuristic: bool) -> Self {
+        self.heuristic = heuristic;
+        self
+    }
+
+    fn n_rows(&self) -> usize {
+        self.weights.len()
+    }
+
+    fn n_cols(&self) -> usize {
+        self.weights.first().map_or(0, |line| line.len())
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        point.row >= 0
+            && point.col >= 0
+            && (point.row as usize) < self.n_rows()
+            && (point.col as usize) < self.n_cols()
+    }
+
+    /// Position of a state in the dense tables
+    fn index(&self, state: &State) -> usize {
+        let tile = state.point.row as usize * self.n_cols() + state.point.col as usize;
+        (tile * 4 + state.dir as usize) * (self.rule.max_run() + 1) + state.run
+    }
+
+    fn state(&self, index: usize) -> State {
+        let runs = self.rule.max_run() + 1;
+        let (tile, run) = (index / runs / 4, index % runs);
+        let dir = Dir::ALL[index / runs % 4];
+        let point = Point::new(
+            (tile / self.n_cols()) as isize,
+            (tile % self.n_cols()) as isize,
+        );
+        State { point, dir, run }
+    }
+
+    /// States reachable in one move, with the move cost
+    fn successors(&self, state: &State) -> Vec<(State, u32)> {
+        let mut moves = Vec::new();
+        let can_turn = state.run == 0 || state.run >= self.rule.min_run();
+        if state.run == 0 {
+            moves.extend(Dir::ALL.iter().map(|&dir| (dir, Move::Straight)));
+        } else {
+            if state.run < self.rule.max_run() {
+                moves.push((state.dir, Move::Straight));
+            }
+            if can_turn {
+                moves.push((state.dir.left(), Move::Turn));
+                moves.push((state.dir.right(), Move::Turn));
+                if self.rule.allow_reverse() {
+                    moves.push((state.dir.reverse(), Move::Reverse));
+                }
+            }
+        }
+
+        let mut res = Vec::new();
+        for (dir, movement) in moves {
+            let point = state.point + dir.delta();
+            if !self.contains(point) {
+                continue;
+            }
+            let weight = self.weights[point.row as usize][point.col as usize];
+            let Some(cost) = self.rule.cost(weight, movement) else {
+                continue;
+            };
+            let run = match movement {
+                Move::Straight => state.run + 1,
+                _ => 1,
+            };
+            res.push((State { point, dir, run }, cost));
+        }
+        res
+    }
+
+    /// Cheapest path from any of `starts` to any of `goals`
+    pub fn run(&self, starts: &[Point], goals: &[Point]) -> Option<Path> {
+        let n_states = self.n_rows() * self.n_cols() * 4 * (self.rule.max_run() + 1);
+        let mut dist = vec![u32::MAX; n_states];
+        let mut prev = vec![usize::MAX; n_states];
+        let mut is_goal = vec![false; self.n_rows() * self.n_cols()];
+        for goal in goals.iter().filter(|&&g| self.contains(g)) {
+            is_goal[goal.row as usize * self.n_cols() + goal.col as usize] = true;
+        }
+
+        // cheapest possible move, so Manhattan distance times it never overestimates
+        let min_cost = self
+            .weights
+            .iter()
+            .flatten()
+            .flat_map(|&w| {
+                [Move::Straight, Move::Turn, Move::Reverse].map(|m| self.rule.cost(w, m))
+            })
+            .flatten()
+            .min()
+            .unwrap_or(0);
+        let heuristic = |point: Point| -> u32 {
+            if !self.heuristic {
+                return 0;
+            }
+            let manhattan = goals
+                .iter()
+                .map(|g| (g.row - point.row).unsigned_abs() + (g.col - point.col).unsigned_abs())
+                .min()
+                .unwrap_or(0);
+            manhattan as u32 * min_cost
+        };
+
+        let mut frontier = match self.queue {
+            Queue::Heap => Frontier::Heap(BinaryHeap::new()),
+            Queue::Buckets => Frontier::Buckets {
+                buckets: Vec::new(),
+                current: 0,
+            },
+        };
+        for &point in starts.iter().filter(|&&p| self.contains(p)) {
+            let state = State {
+                point,
+                dir: Dir::North,
+                run: 0,
+            };
+            let index = self.index(&state);
+            dist[index] = 0;
+            frontier.push(heuristic(point), index);
+        }
+
+        while let Some((priority, index)) = frontier.pop() {
+            let state = self.state(index);
+            let cost = dist[index];
+            if priority > cost + heuristic(state.point) {
+                continue;
+            }
+            let tile = state.point.row as usize * self.n_cols() + state.point.col as usize;
+            if is_goal[tile] && (state.run == 0 || state.run >= self.rule.min_run()) {
+                let mut states = vec![state];
+                let mut current = index;
+                while prev[current] != usize::MAX {
+                    current = prev[current];
+                    states.push(self.state(current));
+                }
+                states.reverse();
+                return Some(Path { cost, states });
+            }
+            for (next, step_cost) in self.successors(&state) {
+                let next_index = self.index(&next);
+                let next_cost = cost + step_cost;
+                if next_cost < dist[next_index] {
+                    dist[next_index] = next_cost;
+                    prev[next_index] = index;
+                    frontier.push(next_cost + heuristic(next.point), next_index);
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,225 @@
+use std::str::FromStr;
+
+/// Condition of a single spring
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spring {
+    Operational,
+    Damaged,
+    Unknown,
+}
+
+impl TryFrom<char> for Spring {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Self::Operational),
+            '#' => Ok(Self::Damaged),
+            '?' => Ok(Self::Unknown),
+            _ => Err(format!("invalid spring '{}'", c)),
+        }
+    }
+}
+
+impl std::fmt::Display for Spring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Self::Operational => '.',
+            Self::Damaged => '#',
+            Self::Unknown => '?',
+        };
+        write!(f, "{}", c)
+    }
+}
+
+/// A row of springs together with the sizes of its contiguous groups of damaged springs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpringRow {
+    pub springs: Vec<Spring>,
+    pub groups: Vec<usize>,
+}
+
+impl FromStr for SpringRow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (springs, groups) = s.split_once(' ').ok_or("missing groups")?;
+        let springs = springs
+            .chars()
+            .map(Spring::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let groups = groups
+            .split(',')
+            .map(|g| g.parse().map_err(|_| format!("invalid group '{}'", g)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { springs, groups })
+    }
+}
+
+impl std::fmt::Display for SpringRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for spring in self.springs.iter() {
+            write!(f, "{}", spring)?;
+        }
+        let groups = self.groups.iter().map(|g| g.to_string());
+        write!(f, " {}", groups.collect::<Vec<_>>().join(","))
+    }
+}
+
+impl SpringRow {
+    /// Repeats the springs `factor` times (joined by an unknown spring) and the groups `factor` times
+    pub fn unfold(&self, factor: usize) -> Self {
+        let mut springs = Vec::new();
+        for i in 0..factor {
+            if i > 0 {
+                springs.push(Spring::Unknown);
+            }
+            springs.extend_from_slice(&self.springs);
+        }
+        let groups = self.groups.repeat(factor);
+        Self { springs, groups }
+    }
+
+    /// `runs[i]` is the number of consecutive non-operational springs starting at `i`
+    fn runs(&self) -> Vec<usize> {
+        let mut runs = vec![0; self.springs.len() + 1];
+        for i in (0..self.springs.len()).rev() {
+            if self.springs[i] != Spring::Operational {
+                runs[i] = runs[i + 1] + 1;
+            }
+        }
+        runs
+    }
+
+    /// If a damaged group of length `group` can start at `pos`, returns the position after it
+    /// (skipping the operational spring that must follow)
+    fn fit_group(&self, runs: &[usize], pos: usize, group: usize) -> Option<usize> {
+        let end = pos + group;
+        if runs[pos] < group || self.springs.get(end) == Some(&Spring::Damaged) {
+            return None;
+        }
+        Some(self.springs.len().min(end + 1))
+    }
+
+    /// Fills a position-indexed table where `table[i][j]` combines the ways of arranging
+    /// `springs[i..]` with `groups[j..]`.
+    fn fill_table<T: Copy>(
+        &self,
+        zero: T,
+        one: T,
+        add: impl Fn(T, T) -> Option<T>,
+    ) -> Option<Vec<Vec<T>>> {
+        let (n, m) = (self.springs.len(), self.groups.len());
+        let runs = self.runs();
+        let mut table = vec![vec![zero; m + 1]; n + 1];
+        table[n][m] = one;
+        for i in (0..n).rev() {
+            for j in (0..=m).rev() {
+                let mut res = zero;
+                if self.springs[i] != Spring::Damaged {
+                    res = table[i + 1][j];
+                }
+                if self.springs[i] != Spring::Operational && j < m {
+                    if let Some(next) = self.fit_group(&runs, i, self.groups[j]) {
+                        res = add(res, table[next][j + 1])?;
+                    }
+                }
+                table[i][j] = res;
+            }
+        }
+        Some(table)
+    }
+
+    /// Number of valid arrangements, or `None` if it does not fit in a `u128`
+    pub fn count_arrangements(&self) -> Option<u128> {
+        let table = self.fill_table(0u128, 1, |a, b| a.checked_add(b))?;
+        Some(table[0][0])
+    }
+
+    /// Whether there is at least one valid arrangement
+    pub fn is_feasible(&self) -> bool {
+        let feasible = self.fill_table(false, true, |a, b| Some(a || b));
+        feasible.is_some_and(|table| table[0][0])
+    }
+
+    /// Line solving: resolves every unknown spring that has the same condition in all the
+    /// valid arrangements. Returns `None` if the row has no valid arrangement.
+    pub fn solve_line(&self) -> Option<Vec<Spring>> {
+        if !self.is_feasible() {
+            return None;
+        }
+        let mut res = self.springs.clone();
+        let mut row = self.clone();
+        for (i, cell) in res.iter_mut().enumerate() {
+            if *cell != Spring::Unknown {
+                continue;
+            }
+            row.springs[i] = Spring::Damaged;
+            let can_be_damaged = row.is_feasible();
+            row.springs[i] = Spring::Operational;
+            let can_be_operational = row.is_feasible();
+            row.springs[i] = Spring::Unknown;
+            *cell = match (can_be_damaged, can_be_operational) {
+                (true, false) => Spring::Damaged,
+                (false, true) => Spring::Operational,
+                _ => Spring::Unknown,
+            };
+        }
+        Some(res)
+    }
+
+    /// Lazily enumerates every valid arrangement
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        let feasible = self
+            .fill_table(false, true, |a, b| Some(a || b))
+            .expect("boolean table cannot overflow");
+        let stack = match feasible[0][0] {
+            true => vec![(0, 0, String::new())],
+            false => vec![],
+        };
+        Arrangements {
+            row: self,
+            runs: self.runs(),
+            feasible,
+            stack,
+        }
+    }
+}
+
+/// Iterator over the arrangements of a [`SpringRow`], rendered with `#` and `.`.
+/// Only feasible states are pushed to the stack, so every branch yields an arrangement.
+pub struct Arrangements<'a> {
+    row: &'a SpringRow,
+    runs: Vec<usize>,
+    feasible: Vec<Vec<bool>>,
+    stack: Vec<(usize, usize, String)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (springs, groups) = (&self.row.springs, &self.row.groups);
+        while let Some((i, j, prefix)) = self.stack.pop() {
+            if i == springs.len() {
+                return Some(prefix);
+            }
+            if springs[i] != Spring::Operational && j < groups.len() {
+                if let Some(next) = self.row.fit_group(&self.runs, i, groups[j]) {
+                    if self.feasible[next][j + 1] {
+                        let mut prefix = prefix.clone();
+                        prefix.push_str(&"#".repeat(groups[j]));
+                        if next > i + groups[j] {
+                            prefix.push('.');
+                        }
+                        self.stack.push((next, j + 1, prefix));
+                    }
+                }
+            }
+            if springs[i] != Spring::Damaged && self.feasible[i + 1][j] {
+                self.stack.push((i + 1, j, prefix + "."));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,765 @@
+//! Workflow language of day 19: `name{attr<value:target,...,target}`
+//!
+//! The workflow section may start with directives declaring the attributes of the parts
+//! (`@attributes x,m,a,s`) and the range of their values (`@range 1..4000`). Without them,
+//! attributes are inferred from the parts section and values range over [`DEFAULT_RANGE`].
+
+use std::collections::HashMap;
+
+pub const DEFAULT_RANGE: (usize, usize) = (1, 4_000);
+
+/// Error found in the input, with its (1-based) location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub col: usize,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(line: usize, col: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            col,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.message)
+    }
+}
+
+/// Attribute names of the parts and the (inclusive) range of their values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    pub attributes: Vec<String>,
+    pub range: (usize, usize),
+}
+
+impl Schema {
+    pub fn index(&self, attr: &str) -> Option<usize> {
+        self.attributes.iter().position(|a| a == attr)
+    }
+
+    /// Parses a part line, `{x=1,m=2,...}`, with the attributes in any order
+    pub fn parse_part(&self, text: &str, line: usize) -> Result<Part, Vec<Diagnostic>> {
+        let values = parse_part(text, line).map_err(|diagnostic| vec![diagnostic])?;
+        self.resolve_part(values, line)
+    }
+
+    fn resolve_part(
+        &self,
+        values: Vec<(&str, usize)>,
+        line: usize,
+    ) -> Result<Part, Vec<Diagnostic>> {
+        let mut diagnostics = Vec::new();
+        let mut part = vec![None; self.attributes.len()];
+        for (attr, value) in values {
+            match self.index(attr) {
+                Some(i) => part[i] = Some(value),
+                None => {
+                    let message = format!("unknown attribute '{}'", attr);
+                    diagnostics.push(Diagnostic::new(line, 1, message));
+                }
+            }
+        }
+        match part.into_iter().collect::<Option<Vec<_>>>() {
+            Some(values) if diagnostics.is_empty() => Ok(Part(values)),
+            Some(_) => Err(diagnostics),
+            None => {
+                diagnostics.push(Diagnostic::new(line, 1, "missing attributes"));
+                Err(diagnostics)
+            }
+        }
+    }
+
+    pub fn format_range(&self, range: &PartRange) -> String {
+        let ranges = self.attributes.iter().zip(range.0.iter());
+        let ranges = ranges.map(|(attr, (low, high))| format!("{}={}..{}", attr, low, high));
+        format!("{{{}}}", ranges.collect::<Vec<_>>().join(", "))
+    }
+
+    pub fn format_part(&self, part: &Part) -> String {
+        let values = self.attributes.iter().zip(part.0.iter());
+        let values = values.map(|(attr, value)| format!("{}={}", attr, value));
+        format!("{{{}}}", values.collect::<Vec<_>>().join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    const ALL: [Op; 6] = [Op::Le, Op::Ge, Op::Eq, Op::Ne, Op::Lt, Op::Gt];
+
+    pub fn eval(&self, lhs: usize, rhs: usize) -> bool {
+        match self {
+            Self::Lt => lhs < rhs,
+            Self::Le => lhs <= rhs,
+            Self::Gt => lhs > rhs,
+            Self::Ge => lhs >= rhs,
+            Self::Eq => lhs == rhs,
+            Self::Ne => lhs != rhs,
+        }
+    }
+
+    /// Operator that holds exactly when this one does not
+    pub fn negate(&self) -> Self {
+        match self {
+            Self::Lt => Self::Ge,
+            Self::Le => Self::Gt,
+            Self::Gt => Self::Le,
+            Self::Ge => Self::Lt,
+            Self::Eq => Self::Ne,
+            Self::Ne => Self::Eq,
+        }
+    }
+
+    /// Inclusive intervals of the values `v` such that `v op value` holds
+    pub fn intervals(&self, value: usize) -> Vec<(usize, usize)> {
+        let below = value.checked_sub(1).map(|v| (0, v));
+        let above = value.checked_add(1).map(|v| (v, usize::MAX));
+        match self {
+            Self::Lt => below.into_iter().collect(),
+            Self::Le => vec![(0, value)],
+            Self::Gt => above.into_iter().collect(),
+            Self::Ge => vec![(value, usize::MAX)],
+            Self::Eq => vec![(value, value)],
+            Self::Ne => below.into_iter().chain(above).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    /// Index of the attribute in the [`Schema`]
+    pub attr: usize,
+    pub op: Op,
+    pub value: usize,
+}
+
+/// A part, with its values in [`Schema`] order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part(pub Vec<usize>);
+
+impl Part {
+    pub fn matches(&self, condition: &Option<Condition>) -> bool {
+        condition.is_none_or(|c| c.op.eval(self.0[c.attr], c.value))
+    }
+
+    /// Sum of all the attribute values
+    pub fn rating(&self) -> usize {
+        self.0.iter().sum()
+    }
+}
+
+/// All the parts with values within inclusive ranges, in [`Schema`] order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartRange(pub Vec<(usize, usize)>);
+
+impl PartRange {
+    pub fn new(schema: &Schema) -> Self {
+        Self(vec![schema.range; schema.attributes.len()])
+    }
+
+    /// Number of parts in the range
+    pub fn len(&self) -> u128 {
+        self.0
+            .iter()
+            .map(|(low, high)| (high - low + 1) as u128)
+            .product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Restricts the attribute to the values for which `v op value` holds
+    fn restrict(&self, attr: usize, op: Op, value: usize) -> Vec<Self> {
+        let (low, high) = self.0[attr];
+        let mut res = Vec::new();
+        for (from, to) in op.intervals(value) {
+            let (from, to) = (from.max(low), to.min(high));
+            if from <= to {
+                let mut range = self.clone();
+                range.0[attr] = (from, to);
+                res.push(range);
+            }
+        }
+        res
+    }
+
+    /// Splits the range into the parts that match the condition and the parts that do not
+    pub fn split(&self, condition: &Option<Condition>) -> (Vec<Self>, Vec<Self>) {
+        match condition {
+            Some(Condition { attr, op, value }) => (
+                self.restrict(*attr, *op, *value),
+                self.restrict(*attr, op.negate(), *value),
+            ),
+            None => (vec![self.clone()], vec![]),
+        }
+    }
+}
+
+/// Interned workflow name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WorkflowId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    Accept,
+    Reject,
+    Workflow(WorkflowId),
+}
+
+/// `condition -> target`. Rules without condition always apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub condition: Option<Condition>,
+    pub target: Target,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workflow {
+    pub name: String,
+    pub rules: Vec<Rule>,
+    /// Line where the workflow is defined
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workflows {
+    /// Indexed by [`WorkflowId`]
+    pub workflows: Vec<Workflow>,
+    pub ids: HashMap<String, WorkflowId>,
+    /// The `in` workflow
+    pub start: WorkflowId,
+    pub schema: Schema,
+}
+
+impl std::ops::Index<WorkflowId> for Workflows {
+    type Output = Workflow;
+
+    fn index(&self, id: WorkflowId) -> &Self::Output {
+        &self.workflows[id.0]
+    }
+}
+
+/// Rule as written in the input, before resolving its target
+struct RawRule<'a> {
+    condition: Option<Condition>,
+    target: &'a str,
+    col: usize,
+}
+
+fn parse_condition(
+    s: &str,
+    line: usize,
+    col: usize,
+    schema: &Schema,
+) -> Result<Condition, Diagnostic> {
+    let attr_len = s.find(|c: char| !c.is_ascii_alphanumeric() && c != '_');
+    let attr_len = attr_len.unwrap_or(s.len());
+    let (attr, rest) = s.split_at(attr_len);
+    let attr = schema
+        .index(attr)
+        .ok_or_else(|| Diagnostic::new(line, col, format!("unknown attribute '{}'", attr)))?;
+    let col = col + attr_len;
+    let op = Op::ALL
+        .into_iter()
+        .find(|op| rest.starts_with(&op.to_string()))
+        .ok_or_else(|| Diagnostic::new(line, col, format!("invalid operator in '{}'", rest)))?;
+    let value = &rest[op.to_string().len()..];
+    let col = col + op.to_string().len();
+    let value = value
+        .parse()
+        .map_err(|_| Diagnostic::new(line, col, format!("invalid value '{}'", value)))?;
+    Ok(Condition { attr, op, value })
+}
+
+/// Splits `name{rules}` into the name and its raw rules. Errors in a rule do not
+/// prevent parsing the rest, so the workflow name can still be resolved.
+type RawWorkflow<'a> = (&'a str, Vec<Result<RawRule<'a>, Diagnostic>>);
+
+fn parse_workflow<'a>(
+    s: &'a str,
+    line: usize,
+    schema: &Schema,
+) -> Result<RawWorkflow<'a>, Diagnostic> {
+    let open = s
+        .find('{')
+        .ok_or_else(|| Diagnostic::new(line, 1, "expected '{'"))?;
+    let body = s[open + 1..]
+        .strip_suffix('}')
+        .ok_or_else(|| Diagnostic::new(line, s.len() + 1, "expected '}'"))?;
+    let name = &s[..open];
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(Diagnostic::new(
+            line,
+            1,
+            format!("invalid workflow name '{}'", name),
+        ));
+    }
+
+    let mut rules = Vec::new();
+    let mut col = open + 2;
+    for rule in body.split(',') {
+        let raw = match rule.split_once(':') {
+            Some((condition, target)) => {
+                parse_condition(condition, line, col, schema).map(|parsed| RawRule {
+                    condition: Some(parsed),
+                    target,
+                    col: col + condition.len() + 1,
+                })
+            }
+            None => Ok(RawRule {
+                condition: None,
+                target: rule,
+                col,
+            }),
+        };
+        rules.push(raw.and_then(|raw| match raw.target.is_empty() {
+            true => Err(Diagnostic::new(line, raw.col, "missing target")),
+            false => Ok(raw),
+        }));
+        col += rule.len() + 1;
+    }
+    Ok((name, rules))
+}
+
+impl Workflows {
+    /// Parses the workflows, given as `(line number, text)`.
+    /// Returns every problem found: syntax errors, unknown or missing workflows and cycles.
+    pub fn parse(lines: &[(usize, &str)], schema: Schema) -> Result<Self, Vec<Diagnostic>> {
+        let mut diagnostics = Vec::new();
+        let mut parsed = Vec::new();
+        let mut ids = HashMap::new();
+        let first_line = lines.first().map_or(1, |&(line, _)| line);
+
+        // first pass: syntax and workflow names
+        for &(line, text) in lines {
+            match parse_workflow(text, line, &schema) {
+                Ok((name, rules)) => {
+                    if ids.contains_key(name) {
+                        let message = format!("duplicate workflow '{}'", name);
+                        diagnostics.push(Diagnostic::new(line, 1, message));
+                        continue;
+                    }
+                    ids.insert(name.to_string(), WorkflowId(parsed.len()));
+                    parsed.push((name, rules, line));
+                }
+                Err(diagnostic) => diagnostics.push(diagnostic),
+            }
+        }
+
+        // second pass: resolve targets
+        let mut workflows = Vec::new();
+        for (name, raw_rules, line) in parsed {
+            let mut rules = Vec::new();
+            let mut broken = false;
+            for raw in raw_rules {
+                let raw = match raw {
+                    Ok(raw) => raw,
+                    Err(diagnostic) => {
+                        diagnostics.push(diagnostic);
+                        broken = true;
+                        continue;
+                    }
+                };
+                let target = match raw.target {
+                    "A" => Target::Accept,
+                    "R" => Target::Reject,
+                    other => match ids.get(other) {
+                        Some(&id) => Target::Workflow(id),
+                        None => {
+                            let message = format!("unknown workflow '{}'", other);
+                            diagnostics.push(Diagnostic::new(line, raw.col, message));
+                            broken = true;
+                            continue;
+                        }
+                    },
+                };
+                rules.push(Rule {
+                    condition: raw.condition,
+                    target,
+                });
+            }
+            if !broken && rules.last().is_none_or(|r| r.condition.is_some()) {
+                let message = format!("workflow '{}' does not end with a default rule", name);
+                diagnostics.push(Diagnostic::new(line, 1, message));
+            }
+            workflows.push(Workflow {
+                name: name.to_string(),
+                rules,
+                line,
+            });
+        }
+
+        let Some(&start) = ids.get("in") else {
+            diagnostics.push(Diagnostic::new(first_line, 1, "missing 'in' workflow"));
+            diagnostics.sort_by_key(|d| (d.line, d.col));
+            return Err(diagnostics);
+        };
+        let res = Self {
+            workflows,
+            ids,
+            start,
+            schema,
+        };
+        if let Some(cycle) = res.find_cycle() {
+            let names = cycle.iter().map(|&id| res[id].name.as_str());
+            let message = format!(
+                "cycle between workflows: {}",
+                names.collect::<Vec<_>>().join(" -> ")
+            );
+            diagnostics.push(Diagnostic::new(res[cycle[0]].line, 1, message));
+        }
+
+        diagnostics.sort_by_key(|d| (d.line, d.col));
+        match diagnostics.is_empty() {
+            true => Ok(res),
+            false => Err(diagnostics),
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<WorkflowId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn target_name(&self, target: Target) -> &str {
+        match target {
+            Target::Accept => "A",
+            Target::Reject => "R",
+            Target::Workflow(id) => self[id].name.as_str(),
+        }
+    }
+
+    pub fn format_condition(&self, condition: &Condition) -> String {
+        let attr = &self.schema.attributes[condition.attr];
+        format!("{}{}{}", attr, condition.op, condition.value)
+    }
+
+    /// Workflows directly reachable from the given one
+    pub fn successors(&self, id: WorkflowId) -> impl Iterator<Item = WorkflowId> + '_ {
+        self[id].rules.iter().filter_map(|rule| match rule.target {
+            Target::Workflow(next) => Some(next),
+            _ => None,
+        })
+    }
+
+    /// A cycle in the workflow graph, if any (the first workflow is repeated at the end)
+    pub fn find_cycle(&self) -> Option<Vec<WorkflowId>> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum Color {
+            White,
+            Gray,
+            Black,
+        }
+        let mut color = vec![Color::White; self.workflows.len()];
+        for root in 0..self.workflows.len() {
+            if color[root] != Color::White {
+                continue;
+            }
+            // stack of (workflow, index of the next successor to visit)
+            let mut stack = vec![(WorkflowId(root), 0)];
+            color[root] = Color::Gray;
+            while let Some((id, child)) = stack.pop() {
+                let Some(next) = self.successors(id).nth(child) else {
+                    color[id.0] = Color::Black;
+                    continue;
+                };
+                stack.push((id, child + 1));
+                match color[next.0] {
+                    Color::White => {
+                        color[next.0] = Color::Gray;
+                        stack.push((next, 0));
+                    }
+                    Color::Gray => {
+                        let pos = stack.iter().position(|&(w, _)| w == next).unwrap();
+                        let mut cycle = stack[pos..].iter().map(|&(w, _)| w).collect::<Vec<_>>();
+                        cycle.push(next);
+                        return Some(cycle);
+                    }
+                    Color::Black => {}
+                }
+            }
+        }
+        None
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.workflows.iter().map(|w| w.rules.len()).sum()
+    }
+
+    /// Graphviz description of the workflow graph, with an edge per rule
+    pub fn to_dot(&self) -> String {
+        let node = |target: Target| match target {
+            Target::Accept => "accept".to_string(),
+            Target::Reject => "reject".to_string(),
+            Target::Workflow(id) => format!("w{}", id.0),
+        };
+        let mut res = String::from("digraph workflows {\n");
+        res.push_str("  accept [label=\"A\", shape=doublecircle];\n");
+        res.push_str("  reject [label=\"R\", shape=circle];\n");
+        for (i, workflow) in self.workflows.iter().enumerate() {
+            let shape = if WorkflowId(i) == self.start {
+                "box, peripheries=2"
+            } else {
+                "box"
+            };
+            res.push_str(&format!(
+                "  w{} [label=\"{}\", shape={}];\n",
+                i, workflow.name, shape
+            ));
+            for rule in workflow.rules.iter() {
+                let label = match rule.condition {
+                    Some(condition) => self.format_condition(&condition),
+                    None => "else".to_string(),
+                };
+                res.push_str(&format!(
+                    "  w{} -> {} [label=\"{}\"];\n",
+                    i,
+                    node(rule.target),
+                    label
+                ));
+            }
+        }
+        res.push_str("}\n");
+        res
+    }
+
+    /// Equivalent workflows with redundant rules removed: conditional rules followed by a
+    /// rule with the same target (`a<10:R,R` becomes `R`), and workflows that only forward
+    /// to another target, which are inlined. Unreachable workflows are dropped.
+    pub fn minimise(&self) -> Self {
+        let mut workflows = self.workflows.clone();
+        loop {
+            let mut changed = false;
+            for workflow in workflows.iter_mut() {
+                // the last rule is the default one, so the rule before it is redundant
+                let rules = &mut workflow.rules;
+                while rules.len() >= 2
+                    && rules[rules.len() - 2].target == rules[rules.len() - 1].target
+                {
+                    rules.remove(rules.len() - 2);
+                    changed = true;
+                }
+            }
+
+            // workflows made of a single default rule are replaced by their target
+            let forward = |target: Target, workflows: &[Workflow]| match target {
+                Target::Workflow(id) if id != self.start => match workflows[id.0].rules[..] {
+                    [Rule {
+                        condition: None,
+                        target,
+                    }] => Some(target),
+                    _ => None,
+                },
+                _ => None,
+            };
+            for w in 0..workflows.len() {
+                for r in 0..workflows[w].rules.len() {
+                    if let Some(target) = forward(workflows[w].rules[r].target, &workflows) {
+                        workflows[w].rules[r].target = target;
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        // keep the reachable workflows only, renumbered in their original order
+        let mut reachable = vec![false; workflows.len()];
+        let mut stack = vec![self.start];
+        reachable[self.start.0] = true;
+        while let Some(id) = stack.pop() {
+            for rule in workflows[id.0].rules.iter() {
+                if let Target::Workflow(next) = rule.target {
+                    if !reachable[next.0] {
+                        reachable[next.0] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        let mut new_ids = vec![None; workflows.len()];
+        let mut kept = Vec::new();
+        for (i, workflow) in workflows.into_iter().enumerate() {
+            if reachable[i] {
+                new_ids[i] = Some(WorkflowId(kept.len()));
+                kept.push(workflow);
+            }
+        }
+        for rule in kept.iter_mut().flat_map(|w| w.rules.iter_mut()) {
+            if let Target::Workflow(id) = rule.target {
+                rule.target = Target::Workflow(new_ids[id.0].unwrap());
+            }
+        }
+        let ids = kept
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (w.name.clone(), WorkflowId(i)))
+            .collect();
+        Self {
+            workflows: kept,
+            ids,
+            start: new_ids[self.start.0].unwrap(),
+            schema: self.schema.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Workflows {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for workflow in self.workflows.iter() {
+            let rules = workflow.rules.iter().map(|rule| {
+                let target = self.target_name(rule.target);
+                match rule.condition {
+                    Some(condition) => format!("{}:{}", self.format_condition(&condition), target),
+                    None => target.to_string(),
+                }
+            });
+            writeln!(
+                f,
+                "{}{{{}}}",
+                workflow.name,
+                rules.collect::<Vec<_>>().join(",")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the `attr=value` pairs of a part: `{x=1,m=2,...}`
+fn parse_part(s: &str, line: usize) -> Result<Vec<(&str, usize)>, Diagnostic> {
+    let body = s
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .ok_or_else(|| Diagnostic::new(line, 1, "expected '{...}'"))?;
+    let mut res = Vec::new();
+    let mut col = 2;
+    for pair in body.split(',') {
+        let (attr, value) = pair
+            .split_once('=')
+            .ok_or_else(|| Diagnostic::new(line, col, "expected 'attribute=value'"))?;
+        let value = value.parse().map_err(|_| {
+            Diagnostic::new(
+                line,
+                col + attr.len() + 1,
+                format!("invalid value '{}'", value),
+            )
+        })?;
+        res.push((attr, value));
+        col += pair.len() + 1;
+    }
+    Ok(res)
+}
+
+/// Parses the `@attributes` and `@range` directives
+fn parse_directive(text: &str, line: usize, schema: &mut Schema) -> Result<(), Diagnostic> {
+    let (name, args) = text.split_once(' ').unwrap_or((text, ""));
+    match name {
+        "@attributes" => {
+            schema.attributes = args.split(',').map(|a| a.trim().to_string()).collect();
+            Ok(())
+        }
+        "@range" => {
+            let range = args.trim().split_once("..").and_then(|(low, high)| {
+                let (low, high) = (low.parse().ok()?, high.parse().ok()?);
+                (low <= high).then_some((low, high))
+            });
+            schema.range = range
+                .ok_or_else(|| Diagnostic::new(line, name.len() + 2, "expected 'low..high'"))?;
+            Ok(())
+        }
+        _ => Err(Diagnostic::new(
+            line,
+            1,
+            format!("unknown directive '{}'", name),
+        )),
+    }
+}
+
+/// Parses a whole input file: directives, workflows, an empty line and parts
+pub fn parse(input: &str) -> Result<(Workflows, Vec<Part>), Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    let mut lines = input.lines().enumerate().map(|(i, text)| (i + 1, text));
+
+    let mut schema = Schema {
+        attributes: Vec::new(),
+        range: DEFAULT_RANGE,
+    };
+    let mut workflow_lines = Vec::new();
+    for (line, text) in lines.by_ref() {
+        if text.is_empty() {
+            break;
+        }
+        match text.starts_with('@') {
+            true => {
+                if let Err(diagnostic) = parse_directive(text, line, &mut schema) {
+                    diagnostics.push(diagnostic);
+                }
+            }
+            false => workflow_lines.push((line, text)),
+        }
+    }
+
+    let mut raw_parts = Vec::new();
+    for (line, text) in lines {
+        match parse_part(text, line) {
+            Ok(values) => raw_parts.push((line, values)),
+            Err(diagnostic) => diagnostics.push(diagnostic),
+        }
+    }
+    if schema.attributes.is_empty() {
+        for (attr, _) in raw_parts.iter().flat_map(|(_, values)| values.iter()) {
+            if schema.index(attr).is_none() {
+                schema.attributes.push(attr.to_string());
+            }
+        }
+    }
+
+    let mut parts = Vec::new();
+    for (line, values) in raw_parts {
+        match schema.resolve_part(values, line) {
+            Ok(part) => parts.push(part),
+            Err(errors) => diagnostics.extend(errors),
+        }
+    }
+
+    match Workflows::parse(&workflow_lines, schema) {
+        Ok(workflows) if diagnostics.is_empty() => Ok((workflows, parts)),
+        Ok(_) => Err(diagnostics),
+        Err(errors) => {
+            diagnostics.extend(errors);
+            diagnostics.sort_by_key(|d| (d.line, d.col));
+            Err(diagnostics)
+        }
+    }
+}